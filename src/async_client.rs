@@ -0,0 +1,471 @@
+use futures::{future, Future, Stream};
+use reqwest::unstable::async as reqwest_async;
+use serde;
+use std::collections::HashMap;
+use tokio_core::reactor::Handle;
+
+use endpoints;
+use request::Request;
+use transport::{self, Response, Transport};
+use types::*;
+use {
+    BatchParams, ChartParams, DailyStatsParams, Duration, IexClientBuilder, IexError, ListParams,
+    MarketList, NewsParams, Result,
+};
+
+pub type IexFuture<T> = Box<Future<Item = T, Error = IexError>>;
+
+/// Performs the request/response step for an `AsyncIexClient`.
+///
+/// Every blocking `Transport` is also an `AsyncTransport` whose futures resolve immediately, so
+/// the in-memory and cassette transports work with both clients.
+pub trait AsyncTransport {
+    fn get(&self, path: &str, query: &str) -> IexFuture<Response>;
+}
+
+impl<T: Transport> AsyncTransport for T {
+    fn get(&self, path: &str, query: &str) -> IexFuture<Response> {
+        Box::new(future::result(Transport::get(self, path, query)))
+    }
+}
+
+/// The default async transport, which talks to the IEX API over HTTP using reqwest's async client.
+pub struct AsyncHttpTransport {
+    http: reqwest_async::Client,
+    base_url: String,
+}
+
+impl AsyncHttpTransport {
+    /// `base_url` should include the API version, e.g. `https://api.iextrading.com/1.0`.
+    pub fn new<S: Into<String>>(http: reqwest_async::Client, base_url: S) -> AsyncHttpTransport {
+        AsyncHttpTransport {
+            http: http,
+            base_url: base_url.into(),
+        }
+    }
+}
+
+impl AsyncTransport for AsyncHttpTransport {
+    fn get(&self, path: &str, query: &str) -> IexFuture<Response> {
+        let url = transport::url(&self.base_url, path, query);
+        Box::new(
+            self.http
+                .get(&url)
+                .send()
+                .and_then(|res| {
+                    let status = res.status().as_u16();
+                    res.into_body()
+                        .fold(Vec::new(), |mut body, chunk| {
+                            body.extend_from_slice(&chunk);
+                            Ok::<_, ::reqwest::Error>(body)
+                        })
+                        .map(move |body| {
+                            Response::new(status, String::from_utf8_lossy(&body).into_owned())
+                        })
+                })
+                .map_err(IexError::from),
+        )
+    }
+}
+
+/// A non-blocking client for the IEX API, with the same endpoints as `IexClient`.
+///
+/// Requests are built and responses parsed by the same code as `IexClient`; only the transport
+/// differs.
+pub struct AsyncIexClient {
+    transport: Box<AsyncTransport>,
+}
+
+impl AsyncIexClient {
+    pub fn new(handle: &Handle) -> Result<Self> {
+        IexClientBuilder::default().build_async(handle)
+    }
+
+    pub fn with_transport<T>(transport: T) -> Self
+    where
+        T: AsyncTransport + 'static,
+    {
+        AsyncIexClient {
+            transport: Box::new(transport),
+        }
+    }
+
+    pub fn batch(&self, params: BatchParams) -> IexFuture<HashMap<String, BatchResult>> {
+        self.try_execute(endpoints::batch(params))
+    }
+
+    pub fn symbol_batch(&self, symbol: &str, params: BatchParams) -> IexFuture<BatchResult> {
+        self.try_execute(endpoints::symbol_batch(symbol, params))
+    }
+
+    pub fn book(&self, symbol: &str) -> IexFuture<Book> {
+        self.execute(endpoints::book(symbol))
+    }
+
+    pub fn chart(&self, symbol: &str, duration: Duration) -> IexFuture<Chart> {
+        self.chart_with_params(symbol, duration, ChartParams::default())
+    }
+
+    pub fn chart_with_params(
+        &self,
+        symbol: &str,
+        duration: Duration,
+        params: ChartParams,
+    ) -> IexFuture<Chart> {
+        self.try_execute(endpoints::chart(symbol, duration, params))
+    }
+
+    pub fn chart_by_date(&self, symbol: &str, date: &str) -> IexFuture<Vec<MinuteBar>> {
+        self.chart_by_date_with_params(symbol, date, ChartParams::default())
+    }
+
+    pub fn chart_by_date_with_params(
+        &self,
+        symbol: &str,
+        date: &str,
+        params: ChartParams,
+    ) -> IexFuture<Vec<MinuteBar>> {
+        self.try_execute(endpoints::chart_by_date(symbol, date, params))
+    }
+
+    pub fn company(&self, symbol: &str) -> IexFuture<Company> {
+        self.execute(endpoints::company(symbol))
+    }
+
+    pub fn crypto(&self) -> IexFuture<Vec<Quote>> {
+        self.execute(endpoints::crypto())
+    }
+
+    pub fn delayed_quote(&self, symbol: &str) -> IexFuture<DelayedQuote> {
+        self.execute(endpoints::delayed_quote(symbol))
+    }
+
+    pub fn dividends(&self, symbol: &str, duration: Duration) -> IexFuture<Vec<Dividend>> {
+        self.execute(endpoints::dividends(symbol, duration))
+    }
+
+    pub fn earnings(&self, symbol: &str) -> IexFuture<Earnings> {
+        self.execute(endpoints::earnings(symbol))
+    }
+
+    pub fn effective_spread(&self, symbol: &str) -> IexFuture<Vec<EffectiveSpread>> {
+        self.execute(endpoints::effective_spread(symbol))
+    }
+
+    pub fn financials(&self, symbol: &str) -> IexFuture<Financials> {
+        self.execute(endpoints::financials(symbol))
+    }
+
+    pub fn iex_regulation_sho_threshold_securities_list(
+        &self,
+        date: Option<&str>,
+    ) -> IexFuture<Vec<IEXRegulationSHOThresholdSecurity>> {
+        self.execute(endpoints::iex_regulation_sho_threshold_securities_list(
+            date,
+        ))
+    }
+
+    pub fn iex_short_interest_list(
+        &self,
+        symbol: Option<&str>,
+        date: Option<&str>,
+    ) -> IexFuture<Vec<IEXShortInterest>> {
+        self.execute(endpoints::iex_short_interest_list(symbol, date))
+    }
+
+    pub fn stats(&self, symbol: &str) -> IexFuture<Stats> {
+        self.execute(endpoints::stats(symbol))
+    }
+
+    pub fn list(&self, list: MarketList) -> IexFuture<Vec<Quote>> {
+        self.list_with_params(list, ListParams::default())
+    }
+
+    pub fn list_with_params(&self, list: MarketList, params: ListParams) -> IexFuture<Vec<Quote>> {
+        self.try_execute(endpoints::list(list, params))
+    }
+
+    pub fn market(&self) -> IexFuture<Vec<MarketVolume>> {
+        self.execute(endpoints::market())
+    }
+
+    pub fn logo(&self, symbol: &str) -> IexFuture<Logo> {
+        self.execute(endpoints::logo(symbol))
+    }
+
+    pub fn news(&self, symbol: &str) -> IexFuture<Vec<News>> {
+        self.news_with_params(NewsParams {
+            symbol: String::from(symbol),
+            last: None,
+        })
+    }
+
+    pub fn news_with_params(&self, params: NewsParams) -> IexFuture<Vec<News>> {
+        self.execute(endpoints::news(params))
+    }
+
+    pub fn ohlc(&self, symbol: &str) -> IexFuture<OHLC> {
+        self.execute(endpoints::ohlc(symbol))
+    }
+
+    pub fn peers(&self, symbol: &str) -> IexFuture<Vec<String>> {
+        self.execute(endpoints::peers(symbol))
+    }
+
+    pub fn previous(&self, symbol: &str) -> IexFuture<Previous> {
+        self.execute(endpoints::previous(symbol))
+    }
+
+    pub fn previous_market(&self) -> IexFuture<HashMap<String, Previous>> {
+        self.execute(endpoints::previous_market())
+    }
+
+    pub fn price(&self, symbol: &str) -> IexFuture<f64> {
+        self.execute(endpoints::price(symbol))
+    }
+
+    pub fn quote(&self, symbol: &str) -> IexFuture<Quote> {
+        self.execute(endpoints::quote(symbol))
+    }
+
+    pub fn relevant(&self, symbol: &str) -> IexFuture<Relevant> {
+        self.execute(endpoints::relevant(symbol))
+    }
+
+    pub fn sector_performance(&self) -> IexFuture<Vec<SectorPerformance>> {
+        self.execute(endpoints::sector_performance())
+    }
+
+    pub fn splits(&self, symbol: &str, duration: Duration) -> IexFuture<Vec<Split>> {
+        self.execute(endpoints::splits(symbol, duration))
+    }
+
+    pub fn time_series(&self, symbol: &str, duration: Duration) -> IexFuture<Chart> {
+        self.time_series_with_params(symbol, duration, ChartParams::default())
+    }
+
+    pub fn time_series_with_params(
+        &self,
+        symbol: &str,
+        duration: Duration,
+        params: ChartParams,
+    ) -> IexFuture<Chart> {
+        self.chart_with_params(symbol, duration, params)
+    }
+
+    pub fn tops(&self, symbols: &[&str]) -> IexFuture<Vec<Tops>> {
+        self.try_execute(endpoints::tops(symbols))
+    }
+
+    pub fn last(&self, symbols: &[&str]) -> IexFuture<Vec<Last>> {
+        self.try_execute(endpoints::last(symbols))
+    }
+
+    pub fn deep(&self, symbol: &str) -> IexFuture<Deep> {
+        self.try_execute(endpoints::deep(symbol))
+    }
+
+    pub fn deep_book(&self, symbols: &[&str]) -> IexFuture<HashMap<String, DeepBook>> {
+        self.try_execute(endpoints::deep_book(symbols))
+    }
+
+    pub fn deep_trades(&self, symbols: &[&str]) -> IexFuture<HashMap<String, Vec<Trade>>> {
+        self.try_execute(endpoints::deep_trades(symbols))
+    }
+
+    pub fn deep_system_event(&self) -> IexFuture<SystemEvent> {
+        self.execute(endpoints::deep_system_event())
+    }
+
+    pub fn deep_trading_status(
+        &self,
+        symbols: &[&str],
+    ) -> IexFuture<HashMap<String, TradingStatus>> {
+        self.try_execute(endpoints::deep_trading_status(symbols))
+    }
+
+    pub fn deep_op_halt_status(
+        &self,
+        symbols: &[&str],
+    ) -> IexFuture<HashMap<String, OpHaltStatus>> {
+        self.try_execute(endpoints::deep_op_halt_status(symbols))
+    }
+
+    pub fn deep_ssr_status(&self, symbols: &[&str]) -> IexFuture<HashMap<String, SSRStatus>> {
+        self.try_execute(endpoints::deep_ssr_status(symbols))
+    }
+
+    pub fn deep_security_event(
+        &self,
+        symbols: &[&str],
+    ) -> IexFuture<HashMap<String, SecurityEvent>> {
+        self.try_execute(endpoints::deep_security_event(symbols))
+    }
+
+    pub fn deep_trade_breaks(&self, symbols: &[&str]) -> IexFuture<HashMap<String, Vec<Trade>>> {
+        self.try_execute(endpoints::deep_trade_breaks(symbols))
+    }
+
+    pub fn deep_auction(&self, symbols: &[&str]) -> IexFuture<HashMap<String, Auction>> {
+        self.try_execute(endpoints::deep_auction(symbols))
+    }
+
+    pub fn deep_official_price(
+        &self,
+        symbols: &[&str],
+    ) -> IexFuture<HashMap<String, OfficialPrice>> {
+        self.try_execute(endpoints::deep_official_price(symbols))
+    }
+
+    pub fn stats_intraday(&self) -> IexFuture<IntradayStats> {
+        self.execute(endpoints::stats_intraday())
+    }
+
+    pub fn stats_recent(&self) -> IexFuture<Vec<DailyStats>> {
+        self.execute(endpoints::stats_recent())
+    }
+
+    pub fn stats_records(&self) -> IexFuture<RecordStats> {
+        self.execute(endpoints::stats_records())
+    }
+
+    pub fn stats_historical(&self, date: Option<&str>) -> IexFuture<Vec<HistoricalStats>> {
+        self.try_execute(endpoints::stats_historical(date))
+    }
+
+    pub fn stats_historical_daily(&self, params: DailyStatsParams) -> IexFuture<Vec<DailyStats>> {
+        self.try_execute(endpoints::stats_historical_daily(params))
+    }
+
+    pub fn volume_by_venue(&self, symbol: &str) -> IexFuture<Vec<VolumeByVenue>> {
+        self.execute(endpoints::volume_by_venue(symbol))
+    }
+
+    pub fn symbols(&self) -> IexFuture<Vec<Symbol>> {
+        self.execute(endpoints::symbols())
+    }
+
+    pub fn iex_symbols(&self) -> IexFuture<Vec<Symbol>> {
+        self.execute(endpoints::iex_symbols())
+    }
+
+    pub fn otc_symbols(&self) -> IexFuture<Vec<Symbol>> {
+        self.execute(endpoints::otc_symbols())
+    }
+
+    pub fn mutual_fund_symbols(&self) -> IexFuture<Vec<Symbol>> {
+        self.execute(endpoints::mutual_fund_symbols())
+    }
+
+    pub fn international_symbols(&self, region: &str) -> IexFuture<Vec<Symbol>> {
+        self.execute(endpoints::international_symbols(region))
+    }
+
+    pub fn corporate_actions(&self, date: Option<&str>) -> IexFuture<Vec<CorporateAction>> {
+        self.execute(endpoints::corporate_actions(date))
+    }
+
+    pub fn daily_list_dividends(&self, date: Option<&str>) -> IexFuture<Vec<DailyListDividend>> {
+        self.execute(endpoints::daily_list_dividends(date))
+    }
+
+    pub fn next_day_ex_date(&self, date: Option<&str>) -> IexFuture<Vec<NextDayExDate>> {
+        self.execute(endpoints::next_day_ex_date(date))
+    }
+
+    pub fn symbol_directory(&self, date: Option<&str>) -> IexFuture<Vec<SymbolDirectoryEntry>> {
+        self.execute(endpoints::symbol_directory(date))
+    }
+
+    pub fn hist(&self) -> IexFuture<HashMap<String, Vec<HistFile>>> {
+        self.execute(endpoints::hist())
+    }
+
+    pub fn hist_by_date(&self, date: &str) -> IexFuture<Vec<HistFile>> {
+        self.try_execute(endpoints::hist_by_date(date))
+    }
+
+    fn execute<R>(&self, request: Request<R>) -> IexFuture<R>
+    where
+        R: serde::de::DeserializeOwned + 'static,
+    {
+        Box::new(
+            self.transport
+                .get(&request.path, &request.query)
+                .and_then(move |res| request.parse(res)),
+        )
+    }
+
+    fn try_execute<R>(&self, request: Result<Request<R>>) -> IexFuture<R>
+    where
+        R: serde::de::DeserializeOwned + 'static,
+    {
+        match request {
+            Ok(request) => self.execute(request),
+            Err(err) => Box::new(future::err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transport::MemoryTransport;
+    use ChartParamsBuilder;
+
+    #[test]
+    fn quote() {
+        let iex = AsyncIexClient::with_transport(MemoryTransport::new().with_json(
+            "/stock/aapl/quote",
+            "",
+            fixture!("quote"),
+        ));
+        let quote = iex.quote("aapl").wait().unwrap();
+        assert_eq!(quote.symbol, "AAPL");
+        assert_eq!(quote.latest_price, 158.73);
+    }
+
+    #[test]
+    fn chart_with_params() {
+        let iex = AsyncIexClient::with_transport(MemoryTransport::new().with_json(
+            "/stock/aapl/chart/ytd",
+            "chartSimplify=true",
+            fixture!("chart"),
+        ));
+        let params = ChartParamsBuilder::default()
+            .chart_simplify(true)
+            .build()
+            .unwrap();
+        let chart = iex
+            .chart_with_params("aapl", Duration::YearToDate, params)
+            .wait()
+            .unwrap();
+        assert_eq!(chart.daily().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn many_symbols() {
+        let iex = AsyncIexClient::with_transport(
+            MemoryTransport::new()
+                .with_json("/stock/aapl/price", "", "143.28")
+                .with_json("/stock/msft/price", "", "74.35"),
+        );
+        let prices = future::join_all(vec![iex.price("aapl"), iex.price("msft")])
+            .wait()
+            .unwrap();
+        assert_eq!(prices, vec![143.28, 74.35]);
+    }
+
+    #[test]
+    fn unknown_symbol() {
+        let iex = AsyncIexClient::with_transport(MemoryTransport::new().with_response(
+            "/stock/zzzz/quote",
+            "",
+            Response::new(404, "Unknown symbol"),
+        ));
+        match iex.quote("zzzz").wait() {
+            Err(IexError::UnknownSymbol { path }) => assert_eq!(path, "/stock/zzzz/quote"),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! How the streaming clients wait between attempts to reconnect, and when they give up.
+
+use std::time::Duration;
+
+/// Settings for reconnecting after a stream's connection drops.
+///
+/// The first attempt waits `delay`, and each failed attempt after it doubles the wait, up to
+/// `max_delay`. An attempt counts as failed until the new connection delivers an update, so a
+/// server that accepts connections and then drops them straight away still uses up the attempts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backoff {
+    pub delay: Duration,
+    pub max_delay: Duration,
+    /// How many failed attempts in a row to make before giving up, or `None` to keep trying.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Backoff {
+        Backoff {
+            delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// How long to wait before attempt `attempt`, counting from zero, or `None` if that's past
+    /// `max_attempts`.
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts.map_or(false, |max| attempt >= max) {
+            return None;
+        }
+        Some(
+            self.delay
+                .checked_mul(1 << attempt.min(16))
+                .map_or(self.max_delay, |delay| delay.min(self.max_delay)),
+        )
+    }
+}
+
+/// Counts the attempts to reconnect since a stream last delivered an update.
+#[derive(Debug)]
+pub(crate) struct Reconnects {
+    backoff: Backoff,
+    attempts: u32,
+}
+
+impl Reconnects {
+    pub fn new(backoff: Backoff) -> Reconnects {
+        Reconnects {
+            backoff: backoff,
+            attempts: 0,
+        }
+    }
+
+    /// Starts another attempt, returning how long to wait before making it, or `None` if it's
+    /// time to give up.
+    pub fn next(&mut self) -> Option<Duration> {
+        let delay = self.backoff.delay(self.attempts)?;
+        self.attempts += 1;
+        Some(delay)
+    }
+
+    /// Records that the connection has delivered an update, so it's working again.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+
+    /// Replaces the delay before the first attempt, e.g. with one the server asked for.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.backoff.delay = delay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_up_to_max_delay() {
+        let backoff = Backoff {
+            delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            max_attempts: None,
+        };
+        let delays: Vec<u64> = (0..5)
+            .map(|attempt| backoff.delay(attempt).unwrap().as_secs())
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+        assert_eq!(backoff.delay(1000), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut reconnects = Reconnects::new(Backoff {
+            max_attempts: Some(2),
+            ..Backoff::default()
+        });
+        assert_eq!(reconnects.next(), Some(Duration::from_secs(1)));
+        assert_eq!(reconnects.next(), Some(Duration::from_secs(2)));
+        assert_eq!(reconnects.next(), None);
+
+        reconnects.reset();
+        reconnects.set_delay(Duration::from_millis(100));
+        assert_eq!(reconnects.next(), Some(Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde_json;
+
+use transport::{Response, Transport};
+use {IexError, Result};
+
+/// A single recorded request and the response the API gave to it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Interaction {
+    pub path: String,
+    pub query: String,
+    pub status: u16,
+    pub body: String,
+}
+
+impl Interaction {
+    fn matches(&self, path: &str, query: &str) -> bool {
+        self.path == path && self.query == query
+    }
+
+    fn response(&self) -> Response {
+        Response::new(self.status, self.body.clone())
+    }
+}
+
+/// A recorded API session, stored on disk as a JSON list of interactions.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Cassette> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| IexError::deserialize(&path.to_string_lossy(), &json, e))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        serde_json::to_writer_pretty(File::create(path)?, self).map_err(io::Error::from)?;
+        Ok(())
+    }
+}
+
+/// Passes requests through to another transport and writes every interaction to a cassette file.
+///
+/// The cassette is rewritten after each request, so it is complete even if the process exits
+/// without dropping the client.
+pub struct RecordingTransport<T> {
+    inner: T,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new<P: Into<PathBuf>>(inner: T, path: P) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner: inner,
+            path: path.into(),
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn get(&self, path: &str, query: &str) -> Result<Response> {
+        let res = self.inner.get(path, query)?;
+        let mut cassette = self.cassette.lock().unwrap();
+        cassette.interactions.push(Interaction {
+            path: String::from(path),
+            query: String::from(query),
+            status: res.status,
+            body: res.body.clone(),
+        });
+        cassette.save(&self.path)?;
+        Ok(res)
+    }
+}
+
+/// Serves responses from a cassette, failing any request that wasn't recorded.
+///
+/// Repeated requests are answered in the order they were recorded. Once those run out, the last
+/// recorded response for the request is served again.
+pub struct ReplayTransport {
+    cassette: Cassette,
+    played: Mutex<Vec<bool>>,
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> ReplayTransport {
+        let played = vec![false; cassette.interactions.len()];
+        ReplayTransport {
+            cassette: cassette,
+            played: Mutex::new(played),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ReplayTransport> {
+        Ok(ReplayTransport::new(Cassette::load(path)?))
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn get(&self, path: &str, query: &str) -> Result<Response> {
+        let mut played = self.played.lock().unwrap();
+        let mut last = None;
+        for (i, interaction) in self.cassette.interactions.iter().enumerate() {
+            if !interaction.matches(path, query) {
+                continue;
+            }
+            if !played[i] {
+                played[i] = true;
+                return Ok(interaction.response());
+            }
+            last = Some(interaction);
+        }
+
+        match last {
+            Some(interaction) => Ok(interaction.response()),
+            None => Err(IexError::Transport(format_err!(
+                "no recorded response for {}?{}",
+                path,
+                query
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use transport::MemoryTransport;
+
+    #[test]
+    fn record_then_replay() {
+        let path = env::temp_dir().join("iex-rs-record-then-replay.json");
+        let memory = MemoryTransport::new()
+            .with_json("/stock/aapl/price", "", "190.5")
+            .with_json("/stock/aapl/chart/1m", "chartSimplify=true", "[]")
+            .with_response(
+                "/stock/msft/price",
+                "",
+                Response::new(404, "Unknown symbol"),
+            );
+
+        let recorder = RecordingTransport::new(memory, path.clone());
+        recorder.get("/stock/aapl/price", "").unwrap();
+        recorder
+            .get("/stock/aapl/chart/1m", "chartSimplify=true")
+            .unwrap();
+        recorder.get("/stock/msft/price", "").unwrap();
+
+        let cassette = Cassette::load(&path).unwrap();
+        assert_eq!(cassette.interactions.len(), 3);
+        assert_eq!(cassette.interactions[1].query, "chartSimplify=true");
+        assert_eq!(cassette.interactions[2].status, 404);
+
+        let replay = ReplayTransport::new(cassette);
+        assert_eq!(
+            replay.get("/stock/aapl/price", "").unwrap(),
+            Response::ok("190.5")
+        );
+        assert_eq!(
+            replay
+                .get("/stock/aapl/chart/1m", "chartSimplify=true")
+                .unwrap(),
+            Response::ok("[]")
+        );
+        assert_eq!(replay.get("/stock/msft/price", "").unwrap().status, 404);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn replay_in_recorded_order() {
+        let interaction = |body: &str| Interaction {
+            path: String::from("/stock/aapl/price"),
+            query: String::new(),
+            status: 200,
+            body: String::from(body),
+        };
+        let replay = ReplayTransport::new(Cassette {
+            interactions: vec![interaction("190.5"), interaction("190.75")],
+        });
+
+        assert_eq!(replay.get("/stock/aapl/price", "").unwrap().body, "190.5");
+        assert_eq!(replay.get("/stock/aapl/price", "").unwrap().body, "190.75");
+        assert_eq!(replay.get("/stock/aapl/price", "").unwrap().body, "190.75");
+    }
+
+    #[test]
+    fn replay_unmatched() {
+        let replay = ReplayTransport::new(Cassette::default());
+        assert!(replay.get("/stock/aapl/price", "").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Request construction for every endpoint, shared by the blocking and async clients.
+
+use std::collections::HashMap;
+
+use request::Request;
+use types::*;
+use {
+    BatchParams, ChartParams, DailyStatsParams, Duration, ListParams, MarketList, NewsParams,
+    Result,
+};
+
+pub fn batch(params: BatchParams) -> Result<Request<HashMap<String, BatchResult>>> {
+    Request::with_params(String::from("/stock/market/batch"), params.query(true))
+}
+
+pub fn book(symbol: &str) -> Request<Book> {
+    Request::new(format!("/stock/{}/book", symbol))
+}
+
+pub fn symbol_batch(symbol: &str, params: BatchParams) -> Result<Request<BatchResult>> {
+    Request::with_params(format!("/stock/{}/batch", symbol), params.query(false))
+}
+
+pub fn chart(symbol: &str, duration: Duration, params: ChartParams) -> Result<Request<Chart>> {
+    let path = format!("/stock/{}/chart/{}", symbol, duration.to_string());
+    Request::with_params(path, params)
+}
+
+pub fn chart_by_date(
+    symbol: &str,
+    date: &str,
+    params: ChartParams,
+) -> Result<Request<Vec<MinuteBar>>> {
+    let path = format!("/stock/{}/chart/date/{}", symbol, date);
+    Request::with_params(path, params)
+}
+
+pub fn company(symbol: &str) -> Request<Company> {
+    Request::new(format!("/stock/{}/company", symbol))
+}
+
+pub fn crypto() -> Request<Vec<Quote>> {
+    Request::new(String::from("/stock/market/crypto"))
+}
+
+pub fn delayed_quote(symbol: &str) -> Request<DelayedQuote> {
+    Request::new(format!("/stock/{}/delayed-quote", symbol))
+}
+
+pub fn dividends(symbol: &str, duration: Duration) -> Request<Vec<Dividend>> {
+    Request::new(format!(
+        "/stock/{}/dividends/{}",
+        symbol,
+        duration.to_string()
+    ))
+}
+
+pub fn earnings(symbol: &str) -> Request<Earnings> {
+    Request::new(format!("/stock/{}/earnings", symbol))
+}
+
+pub fn effective_spread(symbol: &str) -> Request<Vec<EffectiveSpread>> {
+    Request::new(format!("/stock/{}/effective-spread", symbol))
+}
+
+pub fn financials(symbol: &str) -> Request<Financials> {
+    Request::new(format!("/stock/{}/financials", symbol))
+}
+
+pub fn iex_regulation_sho_threshold_securities_list(
+    date: Option<&str>,
+) -> Request<Vec<IEXRegulationSHOThresholdSecurity>> {
+    Request::new(format!(
+        "/stock/market/threshold-securities/{}",
+        date.unwrap_or("")
+    ))
+}
+
+pub fn iex_short_interest_list(
+    symbol: Option<&str>,
+    date: Option<&str>,
+) -> Request<Vec<IEXShortInterest>> {
+    Request::new(format!(
+        "/stock/{}/short-interest/{}",
+        symbol.unwrap_or("market"),
+        date.unwrap_or("")
+    ))
+}
+
+pub fn stats(symbol: &str) -> Request<Stats> {
+    Request::new(format!("/stock/{}/stats", symbol))
+}
+
+pub fn list(list: MarketList, params: ListParams) -> Result<Request<Vec<Quote>>> {
+    let path = format!("/stock/market/list/{}", list.to_string());
+    Request::with_params(path, params)
+}
+
+pub fn market() -> Request<Vec<MarketVolume>> {
+    Request::new(String::from("/market"))
+}
+
+pub fn logo(symbol: &str) -> Request<Logo> {
+    Request::new(format!("/stock/{}/logo", symbol))
+}
+
+pub fn news(params: NewsParams) -> Request<Vec<News>> {
+    match params.last {
+        Some(last) => Request::new(format!("/stock/{}/news/last/{}", params.symbol, last)),
+        None => Request::new(format!("/stock/{}/news", params.symbol)),
+    }
+}
+
+pub fn ohlc(symbol: &str) -> Request<OHLC> {
+    Request::new(format!("/stock/{}/ohlc", symbol))
+}
+
+pub fn peers(symbol: &str) -> Request<Vec<String>> {
+    Request::new(format!("/stock/{}/peers", symbol))
+}
+
+pub fn previous(symbol: &str) -> Request<Previous> {
+    Request::new(format!("/stock/{}/previous", symbol))
+}
+
+pub fn previous_market() -> Request<HashMap<String, Previous>> {
+    Request::new(String::from("/stock/market/previous"))
+}
+
+pub fn price(symbol: &str) -> Request<f64> {
+    Request::new(format!("/stock/{}/price", symbol))
+}
+
+pub fn quote(symbol: &str) -> Request<Quote> {
+    Request::new(format!("/stock/{}/quote", symbol))
+}
+
+pub fn relevant(symbol: &str) -> Request<Relevant> {
+    Request::new(format!("/stock/{}/relevant", symbol))
+}
+
+pub fn sector_performance() -> Request<Vec<SectorPerformance>> {
+    Request::new(String::from("/stock/market/sector-performance"))
+}
+
+pub fn splits(symbol: &str, duration: Duration) -> Request<Vec<Split>> {
+    Request::new(format!("/stock/{}/splits/{}", symbol, duration.to_string()))
+}
+
+pub fn tops(symbols: &[&str]) -> Result<Request<Vec<Tops>>> {
+    Request::with_params(String::from("/tops"), SymbolFilter::new(symbols))
+}
+
+pub fn last(symbols: &[&str]) -> Result<Request<Vec<Last>>> {
+    Request::with_params(String::from("/tops/last"), SymbolFilter::new(symbols))
+}
+
+pub fn deep(symbol: &str) -> Result<Request<Deep>> {
+    Request::with_params(String::from("/deep"), SymbolFilter::new(&[symbol]))
+}
+
+pub fn deep_book(symbols: &[&str]) -> Result<Request<HashMap<String, DeepBook>>> {
+    Request::with_params(String::from("/deep/book"), SymbolFilter::new(symbols))
+}
+
+pub fn deep_trades(symbols: &[&str]) -> Result<Request<HashMap<String, Vec<Trade>>>> {
+    Request::with_params(String::from("/deep/trades"), SymbolFilter::new(symbols))
+}
+
+pub fn deep_system_event() -> Request<SystemEvent> {
+    Request::new(String::from("/deep/system-event"))
+}
+
+pub fn deep_trading_status(symbols: &[&str]) -> Result<Request<HashMap<String, TradingStatus>>> {
+    Request::with_params(
+        String::from("/deep/trading-status"),
+        SymbolFilter::new(symbols),
+    )
+}
+
+pub fn deep_op_halt_status(symbols: &[&str]) -> Result<Request<HashMap<String, OpHaltStatus>>> {
+    Request::with_params(
+        String::from("/deep/op-halt-status"),
+        SymbolFilter::new(symbols),
+    )
+}
+
+pub fn deep_ssr_status(symbols: &[&str]) -> Result<Request<HashMap<String, SSRStatus>>> {
+    Request::with_params(String::from("/deep/ssr-status"), SymbolFilter::new(symbols))
+}
+
+pub fn deep_security_event(symbols: &[&str]) -> Result<Request<HashMap<String, SecurityEvent>>> {
+    Request::with_params(
+        String::from("/deep/security-event"),
+        SymbolFilter::new(symbols),
+    )
+}
+
+pub fn deep_trade_breaks(symbols: &[&str]) -> Result<Request<HashMap<String, Vec<Trade>>>> {
+    Request::with_params(
+        String::from("/deep/trade-breaks"),
+        SymbolFilter::new(symbols),
+    )
+}
+
+pub fn deep_auction(symbols: &[&str]) -> Result<Request<HashMap<String, Auction>>> {
+    Request::with_params(String::from("/deep/auction"), SymbolFilter::new(symbols))
+}
+
+pub fn deep_official_price(symbols: &[&str]) -> Result<Request<HashMap<String, OfficialPrice>>> {
+    Request::with_params(
+        String::from("/deep/official-price"),
+        SymbolFilter::new(symbols),
+    )
+}
+
+pub fn stats_intraday() -> Request<IntradayStats> {
+    Request::new(String::from("/stats/intraday"))
+}
+
+pub fn stats_recent() -> Request<Vec<DailyStats>> {
+    Request::new(String::from("/stats/recent"))
+}
+
+pub fn stats_records() -> Request<RecordStats> {
+    Request::new(String::from("/stats/records"))
+}
+
+pub fn stats_historical(date: Option<&str>) -> Result<Request<Vec<HistoricalStats>>> {
+    Request::with_params(String::from("/stats/historical"), [("date", date)])
+}
+
+pub fn stats_historical_daily(params: DailyStatsParams) -> Result<Request<Vec<DailyStats>>> {
+    Request::with_params(String::from("/stats/historical/daily"), params)
+}
+
+pub fn volume_by_venue(symbol: &str) -> Request<Vec<VolumeByVenue>> {
+    Request::new(format!("/stock/{}/volume-by-venue", symbol))
+}
+
+pub fn symbols() -> Request<Vec<Symbol>> {
+    Request::new(String::from("/ref-data/symbols"))
+}
+
+pub fn iex_symbols() -> Request<Vec<Symbol>> {
+    Request::new(String::from("/ref-data/iex/symbols"))
+}
+
+pub fn otc_symbols() -> Request<Vec<Symbol>> {
+    Request::new(String::from("/ref-data/otc/symbols"))
+}
+
+pub fn mutual_fund_symbols() -> Request<Vec<Symbol>> {
+    Request::new(String::from("/ref-data/mutual-funds/symbols"))
+}
+
+pub fn international_symbols(region: &str) -> Request<Vec<Symbol>> {
+    Request::new(format!("/ref-data/region/{}/symbols", region))
+}
+
+pub fn corporate_actions(date: Option<&str>) -> Request<Vec<CorporateAction>> {
+    Request::new(format!(
+        "/ref-data/daily-list/corporate-actions/{}",
+        date.unwrap_or("")
+    ))
+}
+
+pub fn daily_list_dividends(date: Option<&str>) -> Request<Vec<DailyListDividend>> {
+    Request::new(format!(
+        "/ref-data/daily-list/dividends/{}",
+        date.unwrap_or("")
+    ))
+}
+
+pub fn next_day_ex_date(date: Option<&str>) -> Request<Vec<NextDayExDate>> {
+    Request::new(format!(
+        "/ref-data/daily-list/next-day-ex-date/{}",
+        date.unwrap_or("")
+    ))
+}
+
+pub fn symbol_directory(date: Option<&str>) -> Request<Vec<SymbolDirectoryEntry>> {
+    Request::new(format!(
+        "/ref-data/daily-list/symbol-directory/{}",
+        date.unwrap_or("")
+    ))
+}
+
+pub fn hist() -> Request<HashMap<String, Vec<HistFile>>> {
+    Request::new(String::from("/hist"))
+}
+
+pub fn hist_by_date(date: &str) -> Result<Request<Vec<HistFile>>> {
+    Request::with_params(String::from("/hist"), [("date", date)])
+}
+
+/// The `symbols` query parameter taken by the IEX market data endpoints. An empty list means every
+/// symbol.
+#[derive(Serialize)]
+struct SymbolFilter {
+    symbols: Option<String>,
+}
+
+impl SymbolFilter {
+    fn new(symbols: &[&str]) -> SymbolFilter {
+        SymbolFilter {
+            symbols: if symbols.is_empty() {
+                None
+            } else {
+                Some(symbols.join(","))
+            },
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::io;
+
+use failure;
+use reqwest;
+use serde_json;
+use serde_urlencoded;
+
+use transport::Response;
+
+const SNIPPET_RADIUS: usize = 40;
+
+#[derive(Debug, Fail)]
+pub enum IexError {
+    /// The request didn't complete within the client's timeout.
+    #[fail(display = "request timed out")]
+    Timeout,
+
+    /// The request couldn't be sent, or the response couldn't be read.
+    #[fail(display = "transport error: {}", _0)]
+    Transport(failure::Error),
+
+    /// The API doesn't know the symbol in the request.
+    #[fail(display = "unknown symbol in request to {}", path)]
+    UnknownSymbol { path: String },
+
+    /// The API has rate limited this client.
+    #[fail(display = "rate limited on request to {}: {}", path, body)]
+    RateLimited { path: String, body: String },
+
+    /// The API responded with a status other than success.
+    #[fail(display = "{} returned HTTP status {}: {}", path, status, body)]
+    Status {
+        path: String,
+        status: u16,
+        body: String,
+    },
+
+    /// The response body didn't match the expected type.
+    #[fail(
+        display = "couldn't deserialize response from {}: {} near `{}`",
+        path, cause, snippet
+    )]
+    Deserialize {
+        path: String,
+        snippet: String,
+        #[cause]
+        cause: serde_json::Error,
+    },
+
+    /// The query parameters couldn't be encoded.
+    #[fail(display = "invalid query parameters: {}", _0)]
+    InvalidParams(#[cause] serde_urlencoded::ser::Error),
+
+    /// Binary market data, such as a HIST capture, was malformed.
+    #[fail(display = "couldn't decode market data: {}", _0)]
+    Decode(String),
+
+    /// A streaming connection failed, or the server broke the streaming protocol.
+    #[fail(display = "streaming error: {}", _0)]
+    Stream(String),
+
+    /// A download ended up a different size than the server said it would be.
+    #[fail(
+        display = "download from {} has {} bytes, expected {}",
+        url, actual, expected
+    )]
+    SizeMismatch {
+        url: String,
+        expected: u64,
+        actual: u64,
+    },
+
+    /// Reading or writing a local file, such as a cassette, failed.
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] io::Error),
+}
+
+impl IexError {
+    /// Builds the error for a response with a non-success status.
+    pub fn from_response(path: &str, res: Response) -> IexError {
+        match res.status {
+            404 if res.body.trim() == "Unknown symbol" => IexError::UnknownSymbol {
+                path: String::from(path),
+            },
+            429 => IexError::RateLimited {
+                path: String::from(path),
+                body: res.body,
+            },
+            status => IexError::Status {
+                path: String::from(path),
+                status: status,
+                body: res.body,
+            },
+        }
+    }
+
+    /// Builds the error for a response body that couldn't be deserialized, keeping the part of
+    /// the body serde_json choked on.
+    pub fn deserialize(path: &str, body: &str, cause: serde_json::Error) -> IexError {
+        IexError::Deserialize {
+            path: String::from(path),
+            snippet: snippet(body, cause.line(), cause.column()),
+            cause: cause,
+        }
+    }
+}
+
+impl From<reqwest::Error> for IexError {
+    fn from(err: reqwest::Error) -> IexError {
+        let timed_out = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<io::Error>())
+            .map_or(false, |e| e.kind() == io::ErrorKind::TimedOut);
+        if timed_out {
+            IexError::Timeout
+        } else {
+            IexError::Transport(err.into())
+        }
+    }
+}
+
+impl From<serde_urlencoded::ser::Error> for IexError {
+    fn from(err: serde_urlencoded::ser::Error) -> IexError {
+        IexError::InvalidParams(err)
+    }
+}
+
+impl From<io::Error> for IexError {
+    fn from(err: io::Error) -> IexError {
+        IexError::Io(err)
+    }
+}
+
+// serde_json reports 1-based lines and columns, with column 0 meaning the error was at the start
+// of the line.
+fn snippet(body: &str, line: usize, column: usize) -> String {
+    let line = body.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    let chars: Vec<char> = line.chars().collect();
+    let column = column.min(chars.len());
+    let start = column.saturating_sub(SNIPPET_RADIUS);
+    let end = (column + SNIPPET_RADIUS).min(chars.len());
+    chars[start..end].iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_symbol() {
+        match IexError::from_response("/stock/zzzz/quote", Response::new(404, "Unknown symbol")) {
+            IexError::UnknownSymbol { path } => assert_eq!(path, "/stock/zzzz/quote"),
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn rate_limited() {
+        match IexError::from_response("/stock/aapl/quote", Response::new(429, "Too many requests"))
+        {
+            IexError::RateLimited { body, .. } => assert_eq!(body, "Too many requests"),
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn status() {
+        match IexError::from_response("/stock/aapl/quote", Response::new(500, "oops")) {
+            IexError::Status { status, body, .. } => {
+                assert_eq!(status, 500);
+                assert_eq!(body, "oops");
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn deserialize_snippet() {
+        let body = r#"{"symbol": "AAPL", "price": "not a number", "size": 100}"#;
+        let cause = serde_json::from_str::<::types::Price>(body).unwrap_err();
+        match IexError::deserialize("/stock/aapl/price", body, cause) {
+            IexError::Deserialize { path, snippet, .. } => {
+                assert_eq!(path, "/stock/aapl/price");
+                assert!(snippet.contains("not a number"));
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn snippet_bounds() {
+        assert_eq!(snippet("", 1, 0), "");
+        assert_eq!(snippet("abc", 1, 10), "abc");
+        assert_eq!(snippet("abc\ndef", 2, 1), "def");
+    }
+}
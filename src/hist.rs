@@ -0,0 +1,302 @@
+//! Downloading the HIST captures listed by `IexClient::hist`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use reqwest::{self, header, StatusCode};
+
+use transport::Response;
+use types::HistFile;
+use {IexError, Result};
+
+/// Downloads HIST captures to disk.
+///
+/// A day's capture can run to several gigabytes, so the body is streamed straight to the file
+/// rather than held in memory, and a file left behind by an interrupted download is picked up
+/// where it stopped with a range request.
+pub struct HistDownloader {
+    http: reqwest::Client,
+}
+
+impl HistDownloader {
+    pub fn new() -> Result<HistDownloader> {
+        // The captures are gzipped already and have to be saved byte for byte for their size to
+        // match the listing, so reqwest mustn't decompress them on the way in.
+        let http = reqwest::Client::builder().gzip(false).build()?;
+        Ok(HistDownloader::with_client(http))
+    }
+
+    /// Downloads with `http`, which should have gzip decoding turned off.
+    pub fn with_client(http: reqwest::Client) -> HistDownloader {
+        HistDownloader { http: http }
+    }
+
+    /// Downloads `file` to `path` and returns the number of bytes on disk.
+    ///
+    /// If `path` already holds the start of the capture, only the rest is requested, and if it
+    /// holds all of it nothing is requested at all. A file bigger than the capture is assumed to
+    /// be something else and is replaced. The file is left in place if the download fails, so it
+    /// can be resumed by calling this again.
+    pub fn download<P: AsRef<Path>>(&self, file: &HistFile, path: P) -> Result<u64> {
+        let mut out = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.as_ref())?;
+        let mut offset = out.metadata()?.len();
+        if offset > file.size {
+            out.set_len(0)?;
+            offset = 0;
+        }
+        if offset < file.size {
+            self.fetch(&file.link, &mut out, offset)?;
+        }
+
+        let actual = out.metadata()?.len();
+        if actual != file.size {
+            return Err(IexError::SizeMismatch {
+                url: file.link.clone(),
+                expected: file.size,
+                actual: actual,
+            });
+        }
+        Ok(actual)
+    }
+
+    fn fetch(&self, url: &str, out: &mut File, offset: u64) -> Result<()> {
+        let mut req = self.http.get(url);
+        if offset > 0 {
+            req.header(header::Range::Bytes(vec![header::ByteRangeSpec::AllFrom(
+                offset,
+            )]));
+        }
+        let mut res = req.send()?;
+        match res.status() {
+            StatusCode::PartialContent if range_start(&res) == Some(offset) => {
+                out.seek(SeekFrom::Start(offset))?;
+            }
+            // Any other range can't be appended to what's on disk, even if it's the right length.
+            StatusCode::PartialContent if offset > 0 => {
+                out.set_len(0)?;
+                return self.fetch(url, out, 0);
+            }
+            // Servers are free to ignore the range and send the whole file.
+            StatusCode::Ok => {
+                out.set_len(0)?;
+                out.seek(SeekFrom::Start(0))?;
+            }
+            // What's on disk runs past the end of the server's copy, so it isn't a prefix of it.
+            StatusCode::RangeNotSatisfiable if offset > 0 => {
+                out.set_len(0)?;
+                return self.fetch(url, out, 0);
+            }
+            status => {
+                let body = res.text()?;
+                return Err(IexError::from_response(
+                    url,
+                    Response::new(status.as_u16(), body),
+                ));
+            }
+        }
+
+        let mut writer = BufWriter::new(out);
+        res.copy_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// Where the byte range in a partial response's `Content-Range` header starts.
+fn range_start(res: &reqwest::Response) -> Option<u64> {
+    match res.headers().get::<header::ContentRange>() {
+        Some(&header::ContentRange(header::ContentRangeSpec::Bytes {
+            range: Some((start, _)),
+            ..
+        })) => Some(start),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+    use std::path::PathBuf;
+    use std::thread;
+
+    const CAPTURE: &[u8] = b"not really a gzipped pcap, but the downloader doesn't look inside";
+
+    // Answers a single request for `CAPTURE` like a file server would, honouring a
+    // `Range: bytes=N-` header if `ranges` is set. The thread returns the request's Range header.
+    fn serve(ranges: bool) -> (String, thread::JoinHandle<Option<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/capture.pcap.gz", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let range = read_range(&mut stream);
+            let start = match range {
+                Some(ref range) if ranges => range
+                    .trim_start_matches("bytes=")
+                    .trim_end_matches('-')
+                    .parse()
+                    .unwrap(),
+                _ => 0,
+            };
+            send_capture(&mut stream, start, CAPTURE.len());
+            range
+        });
+        (url, server)
+    }
+
+    // Reads a request's head, returning its Range header.
+    fn read_range(stream: &mut TcpStream) -> Option<String> {
+        let mut head = Vec::new();
+        let mut byte = [0; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+        String::from_utf8(head)
+            .unwrap()
+            .lines()
+            .find(|line| line.to_lowercase().starts_with("range:"))
+            .map(|line| String::from(line[6..].trim()))
+    }
+
+    // Sends `CAPTURE[start..end]`, as a partial response unless it's the whole capture.
+    fn send_capture(stream: &mut TcpStream, start: usize, end: usize) {
+        let mut head = if end - start < CAPTURE.len() {
+            format!(
+                "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\n",
+                start,
+                end - 1,
+                CAPTURE.len()
+            )
+        } else {
+            String::from("HTTP/1.1 200 OK\r\n")
+        };
+        head.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            end - start
+        ));
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(&CAPTURE[start..end]).unwrap();
+    }
+
+    fn hist_file(link: &str, size: u64) -> HistFile {
+        HistFile {
+            link: String::from(link),
+            date: String::from("20180127"),
+            feed: String::from("DEEP"),
+            version: String::from("1.0"),
+            protocol: String::from("IEXTP1"),
+            size: size,
+        }
+    }
+
+    fn temp_path(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("iex-rs-hist-{}.pcap.gz", name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn download(url: &str, size: u64, path: &Path) -> Result<u64> {
+        HistDownloader::new()
+            .unwrap()
+            .download(&hist_file(url, size), path)
+    }
+
+    #[test]
+    fn fresh_download() {
+        let path = temp_path("fresh", b"");
+        let (url, server) = serve(true);
+        assert_eq!(
+            download(&url, CAPTURE.len() as u64, &path).unwrap(),
+            CAPTURE.len() as u64
+        );
+        assert_eq!(server.join().unwrap(), None);
+        assert_eq!(fs::read(&path).unwrap(), CAPTURE);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resumes_partial_download() {
+        let path = temp_path("resume", &CAPTURE[..10]);
+        let (url, server) = serve(true);
+        download(&url, CAPTURE.len() as u64, &path).unwrap();
+        assert_eq!(server.join().unwrap(), Some(String::from("bytes=10-")));
+        assert_eq!(fs::read(&path).unwrap(), CAPTURE);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn server_without_ranges() {
+        let path = temp_path("no-ranges", b"0123456789");
+        let (url, server) = serve(false);
+        download(&url, CAPTURE.len() as u64, &path).unwrap();
+        assert_eq!(server.join().unwrap(), Some(String::from("bytes=10-")));
+        assert_eq!(fs::read(&path).unwrap(), CAPTURE);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn misplaced_range_restarts_download() {
+        let path = temp_path("misplaced", &CAPTURE[..10]);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/capture.pcap.gz", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            // The right number of bytes, but from the start of the capture.
+            let first = {
+                let (mut stream, _) = listener.accept().unwrap();
+                let range = read_range(&mut stream);
+                send_capture(&mut stream, 0, CAPTURE.len() - 10);
+                range
+            };
+            let (mut stream, _) = listener.accept().unwrap();
+            let second = read_range(&mut stream);
+            send_capture(&mut stream, 0, CAPTURE.len());
+            (first, second)
+        });
+
+        download(&url, CAPTURE.len() as u64, &path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), CAPTURE);
+        assert_eq!(
+            server.join().unwrap(),
+            (Some(String::from("bytes=10-")), None)
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn complete_file_isnt_downloaded_again() {
+        let path = temp_path("complete", CAPTURE);
+        // Nothing listens on the discard port, so this would fail if it were requested.
+        download("http://127.0.0.1:9/", CAPTURE.len() as u64, &path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), CAPTURE);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let path = temp_path("mismatch", b"");
+        let (url, server) = serve(true);
+        let expected = CAPTURE.len() as u64 + 5;
+        match download(&url, expected, &path) {
+            Err(IexError::SizeMismatch {
+                expected: e,
+                actual,
+                ..
+            }) => {
+                assert_eq!(e, expected);
+                assert_eq!(actual, CAPTURE.len() as u64);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
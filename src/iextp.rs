@@ -0,0 +1,820 @@
+//! The IEX Transport Protocol (IEX-TP) that carries IEX's market data feeds, and the TOPS and DEEP
+//! messages sent over it.
+//!
+//! Prices are converted from IEX's fixed point representation to `f64`. Messages that map onto one
+//! of the API's response types use its millisecond timestamps; the rest keep IEX-TP's nanoseconds.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use pcap::PacketReader;
+use types::{
+    Ask, Bid, OfficialPrice, OpHaltStatus, SSRStatus, SecurityEvent, SystemEvent, Trade,
+    TradingStatus,
+};
+use {IexError, Result};
+
+pub const TOPS_PROTOCOL_ID: u16 = 0x8003;
+pub const DEEP_PROTOCOL_ID: u16 = 0x8004;
+
+const VERSION: u8 = 1;
+const SEGMENT_HEADER_LEN: usize = 40;
+pub(crate) const PRICE_SCALE: f64 = 10_000.0;
+
+/// The header at the start of every IEX-TP segment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentHeader {
+    pub version: u8,
+    /// `TOPS_PROTOCOL_ID` or `DEEP_PROTOCOL_ID`.
+    pub message_protocol_id: u16,
+    pub channel_id: u32,
+    pub session_id: u32,
+    pub payload_length: u16,
+    pub message_count: u16,
+    /// The byte offset of this segment's payload in the session's stream.
+    pub stream_offset: i64,
+    pub first_message_sequence_number: i64,
+    /// When the segment was sent, in nanoseconds since the Unix epoch.
+    pub send_time: i64,
+}
+
+/// A single IEX-TP segment: one UDP datagram's worth of messages. Segments with no messages are
+/// heartbeats.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub header: SegmentHeader,
+    pub messages: Vec<Message>,
+}
+
+impl Segment {
+    pub fn parse(data: &[u8]) -> Result<Segment> {
+        if data.len() < SEGMENT_HEADER_LEN {
+            return Err(decode_error("truncated IEX-TP segment header"));
+        }
+        let header = SegmentHeader {
+            version: data[0],
+            message_protocol_id: LittleEndian::read_u16(&data[2..]),
+            channel_id: LittleEndian::read_u32(&data[4..]),
+            session_id: LittleEndian::read_u32(&data[8..]),
+            payload_length: LittleEndian::read_u16(&data[12..]),
+            message_count: LittleEndian::read_u16(&data[14..]),
+            stream_offset: LittleEndian::read_i64(&data[16..]),
+            first_message_sequence_number: LittleEndian::read_i64(&data[24..]),
+            send_time: LittleEndian::read_i64(&data[32..]),
+        };
+        if header.version != VERSION {
+            return Err(decode_error(format!(
+                "unsupported IEX-TP version {}",
+                header.version
+            )));
+        }
+
+        let payload = &data[SEGMENT_HEADER_LEN..];
+        let payload_length = usize::from(header.payload_length);
+        if payload.len() < payload_length {
+            return Err(decode_error("truncated IEX-TP segment payload"));
+        }
+        let mut payload = &payload[..payload_length];
+        let mut messages = Vec::with_capacity(usize::from(header.message_count));
+        for _ in 0..header.message_count {
+            if payload.len() < 2 {
+                return Err(decode_error("truncated IEX-TP message block"));
+            }
+            let len = usize::from(LittleEndian::read_u16(payload));
+            if payload.len() < 2 + len {
+                return Err(decode_error("truncated IEX-TP message block"));
+            }
+            messages.push(Message::parse(&payload[2..2 + len])?);
+            payload = &payload[2 + len..];
+        }
+
+        Ok(Segment {
+            header: header,
+            messages: messages,
+        })
+    }
+}
+
+/// A message from the TOPS or DEEP feed. Price level updates and security events are only sent on
+/// DEEP, and quote updates only on TOPS.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    SystemEvent(SystemEvent),
+    SecurityDirectory(SecurityDirectory),
+    TradingStatus {
+        symbol: String,
+        status: TradingStatus,
+    },
+    OperationalHalt {
+        symbol: String,
+        status: OpHaltStatus,
+    },
+    ShortSalePriceTest {
+        symbol: String,
+        status: SSRStatus,
+    },
+    QuoteUpdate(QuoteUpdate),
+    TradeReport {
+        symbol: String,
+        trade: Trade,
+    },
+    OfficialPrice {
+        symbol: String,
+        price: OfficialPrice,
+    },
+    TradeBreak {
+        symbol: String,
+        trade: Trade,
+    },
+    PriceLevelUpdate(PriceLevelUpdate),
+    SecurityEvent {
+        symbol: String,
+        event: SecurityEvent,
+    },
+    AuctionInformation(AuctionInformation),
+    /// A message type this crate doesn't decode, such as retail liquidity indicators.
+    Unknown {
+        message_type: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl Message {
+    /// Decodes a single message, starting with its type byte.
+    pub fn parse(data: &[u8]) -> Result<Message> {
+        let message_type = match data.first() {
+            Some(message_type) => *message_type,
+            None => return Err(decode_error("empty IEX-TP message")),
+        };
+        let expect = |len: usize| {
+            if data.len() < len {
+                Err(decode_error(format!(
+                    "truncated message of type {:?}",
+                    message_type as char
+                )))
+            } else {
+                Ok(())
+            }
+        };
+
+        match message_type {
+            b'S' => {
+                expect(10)?;
+                Ok(Message::SystemEvent(SystemEvent {
+                    system_event: char_string(data[1]),
+                    timestamp: millis(timestamp(data)),
+                }))
+            }
+            b'D' => {
+                expect(31)?;
+                Ok(Message::SecurityDirectory(SecurityDirectory {
+                    symbol: symbol(data),
+                    is_test_security: data[1] & 0x80 != 0,
+                    is_when_issued: data[1] & 0x40 != 0,
+                    is_etp: data[1] & 0x20 != 0,
+                    timestamp: timestamp(data),
+                    round_lot_size: LittleEndian::read_u32(&data[18..]),
+                    adjusted_poc_price: price(&data[22..]),
+                    luld_tier: data[30],
+                }))
+            }
+            b'H' => {
+                expect(22)?;
+                Ok(Message::TradingStatus {
+                    symbol: symbol(data),
+                    status: TradingStatus {
+                        status: char_string(data[1]),
+                        reason: ascii(&data[18..22]),
+                        timestamp: millis(timestamp(data)),
+                    },
+                })
+            }
+            b'O' => {
+                expect(18)?;
+                Ok(Message::OperationalHalt {
+                    symbol: symbol(data),
+                    status: OpHaltStatus {
+                        is_halted: data[1] == b'O',
+                        timestamp: millis(timestamp(data)),
+                    },
+                })
+            }
+            b'P' => {
+                expect(19)?;
+                Ok(Message::ShortSalePriceTest {
+                    symbol: symbol(data),
+                    status: SSRStatus {
+                        is_ssr: data[1] == 1,
+                        detail: char_string(data[18]),
+                        timestamp: millis(timestamp(data)),
+                    },
+                })
+            }
+            b'Q' => {
+                expect(42)?;
+                Ok(Message::QuoteUpdate(QuoteUpdate {
+                    symbol: symbol(data),
+                    is_halted: data[1] & 0x80 != 0,
+                    is_pre_post_market: data[1] & 0x40 != 0,
+                    timestamp: timestamp(data),
+                    bid_size: LittleEndian::read_u32(&data[18..]),
+                    bid_price: price(&data[22..]),
+                    ask_price: price(&data[30..]),
+                    ask_size: LittleEndian::read_u32(&data[38..]),
+                }))
+            }
+            b'T' => {
+                expect(38)?;
+                Ok(Message::TradeReport {
+                    symbol: symbol(data),
+                    trade: trade(data),
+                })
+            }
+            b'X' => {
+                expect(26)?;
+                Ok(Message::OfficialPrice {
+                    symbol: symbol(data),
+                    price: OfficialPrice {
+                        price_type: match data[1] {
+                            b'Q' => String::from("Open"),
+                            b'M' => String::from("Close"),
+                            other => char_string(other),
+                        },
+                        price: price(&data[18..]),
+                        timestamp: millis(timestamp(data)),
+                    },
+                })
+            }
+            b'B' => {
+                expect(38)?;
+                Ok(Message::TradeBreak {
+                    symbol: symbol(data),
+                    trade: trade(data),
+                })
+            }
+            b'8' | b'5' => {
+                expect(30)?;
+                Ok(Message::PriceLevelUpdate(PriceLevelUpdate {
+                    symbol: symbol(data),
+                    side: if message_type == b'8' {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    },
+                    is_event_complete: data[1] & 0x01 != 0,
+                    timestamp: timestamp(data),
+                    size: LittleEndian::read_u32(&data[18..]),
+                    price: price(&data[22..]),
+                }))
+            }
+            b'E' => {
+                expect(18)?;
+                Ok(Message::SecurityEvent {
+                    symbol: symbol(data),
+                    event: SecurityEvent {
+                        security_event: match data[1] {
+                            b'O' => String::from("MarketOpen"),
+                            b'C' => String::from("MarketClose"),
+                            other => char_string(other),
+                        },
+                        timestamp: millis(timestamp(data)),
+                    },
+                })
+            }
+            b'A' => {
+                expect(80)?;
+                Ok(Message::AuctionInformation(AuctionInformation {
+                    symbol: symbol(data),
+                    auction_type: data[1],
+                    timestamp: timestamp(data),
+                    paired_shares: LittleEndian::read_u32(&data[18..]),
+                    reference_price: price(&data[22..]),
+                    indicative_clearing_price: price(&data[30..]),
+                    imbalance_shares: LittleEndian::read_u32(&data[38..]),
+                    imbalance_side: data[42],
+                    extension_number: data[43],
+                    scheduled_auction_time: LittleEndian::read_u32(&data[44..]),
+                    auction_book_clearing_price: price(&data[48..]),
+                    collar_reference_price: price(&data[56..]),
+                    lower_auction_collar: price(&data[64..]),
+                    upper_auction_collar: price(&data[72..]),
+                }))
+            }
+            _ => Ok(Message::Unknown {
+                message_type: message_type,
+                data: data.to_vec(),
+            }),
+        }
+    }
+
+    /// When the message was sent, in milliseconds since the Unix epoch like the API's timestamps.
+    pub fn timestamp(&self) -> Option<f64> {
+        match self {
+            Message::SystemEvent(event) => Some(event.timestamp),
+            Message::SecurityDirectory(directory) => Some(millis(directory.timestamp)),
+            Message::TradingStatus { status, .. } => Some(status.timestamp),
+            Message::OperationalHalt { status, .. } => Some(status.timestamp),
+            Message::ShortSalePriceTest { status, .. } => Some(status.timestamp),
+            Message::QuoteUpdate(quote) => Some(millis(quote.timestamp)),
+            Message::TradeReport { trade, .. } | Message::TradeBreak { trade, .. } => {
+                Some(trade.timestamp)
+            }
+            Message::OfficialPrice { price, .. } => Some(price.timestamp),
+            Message::PriceLevelUpdate(update) => Some(millis(update.timestamp)),
+            Message::SecurityEvent { event, .. } => Some(event.timestamp),
+            Message::AuctionInformation(auction) => Some(millis(auction.timestamp)),
+            Message::Unknown { .. } => None,
+        }
+    }
+}
+
+/// A security IEX trades, sent before the start of each trading day.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityDirectory {
+    pub symbol: String,
+    pub is_test_security: bool,
+    pub is_when_issued: bool,
+    pub is_etp: bool,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub round_lot_size: u32,
+    /// The previous official closing price, adjusted for corporate actions.
+    pub adjusted_poc_price: f64,
+    pub luld_tier: u8,
+}
+
+/// A change to IEX's best bid or offer for a symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteUpdate {
+    pub symbol: String,
+    /// The symbol is halted, paused or otherwise unavailable for trading on IEX.
+    pub is_halted: bool,
+    pub is_pre_post_market: bool,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub bid_size: u32,
+    pub bid_price: f64,
+    pub ask_price: f64,
+    pub ask_size: u32,
+}
+
+impl QuoteUpdate {
+    pub fn bid(&self) -> Bid {
+        Bid {
+            price: self.bid_price,
+            size: f64::from(self.bid_size),
+            timestamp: millis(self.timestamp),
+        }
+    }
+
+    pub fn ask(&self) -> Ask {
+        Ask {
+            price: self.ask_price,
+            size: f64::from(self.ask_size),
+            timestamp: millis(self.timestamp),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// The new aggregate size at a price level on one side of IEX's book. A size of zero removes the
+/// level.
+///
+/// IEX sends a single order book event as several updates, and only the last one has
+/// `is_event_complete` set. The book is only consistent once that last update has been applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceLevelUpdate {
+    pub symbol: String,
+    pub side: Side,
+    pub is_event_complete: bool,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub size: u32,
+    pub price: f64,
+}
+
+/// The state of an IEX auction, sent once a second while it's running.
+///
+/// The single byte codes are as IEX sends them: `auction_type` is `O`pening, `C`losing, `I`PO,
+/// `H`alt or `V`olatility, and `imbalance_side` is `B`uy, `S`ell or `N`o imbalance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuctionInformation {
+    pub symbol: String,
+    pub auction_type: u8,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub paired_shares: u32,
+    pub reference_price: f64,
+    pub indicative_clearing_price: f64,
+    pub imbalance_shares: u32,
+    pub imbalance_side: u8,
+    pub extension_number: u8,
+    /// Seconds since the Unix epoch.
+    pub scheduled_auction_time: u32,
+    pub auction_book_clearing_price: f64,
+    pub collar_reference_price: f64,
+    pub lower_auction_collar: f64,
+    pub upper_auction_collar: f64,
+}
+
+/// Reads the messages in a HIST capture, in the order IEX sent them.
+///
+/// Packets that aren't IEX-TP segments are skipped. The reader stops at the first error.
+pub struct HistReader<R> {
+    packets: PacketReader<R>,
+    messages: vec::IntoIter<Message>,
+    failed: bool,
+}
+
+impl HistReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<HistReader<BufReader<File>>> {
+        HistReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> HistReader<R> {
+    pub fn new(inner: R) -> Result<HistReader<R>> {
+        Ok(HistReader {
+            packets: PacketReader::new(inner)?,
+            messages: Vec::new().into_iter(),
+            failed: false,
+        })
+    }
+
+    /// Reads the next whole segment, including heartbeats, or returns `None` at the end of the
+    /// capture.
+    pub fn next_segment(&mut self) -> Result<Option<Segment>> {
+        while let Some(packet) = self.packets.next_packet()? {
+            if let Some(payload) = packet.udp_payload() {
+                if is_segment(payload) {
+                    return Segment::parse(payload).map(Some);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: Read> Iterator for HistReader<R> {
+    type Item = Result<Message>;
+
+    fn next(&mut self) -> Option<Result<Message>> {
+        loop {
+            if let Some(message) = self.messages.next() {
+                return Some(Ok(message));
+            }
+            if self.failed {
+                return None;
+            }
+            match self.next_segment() {
+                Ok(Some(segment)) => self.messages = segment.messages.into_iter(),
+                Ok(None) => return None,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+fn is_segment(payload: &[u8]) -> bool {
+    payload.len() >= SEGMENT_HEADER_LEN && payload[0] == VERSION && {
+        let protocol = LittleEndian::read_u16(&payload[2..]);
+        protocol == TOPS_PROTOCOL_ID || protocol == DEEP_PROTOCOL_ID
+    }
+}
+
+// Every message but the system event has its flags at byte 1, timestamp at 2 and symbol at 10.
+fn timestamp(data: &[u8]) -> i64 {
+    LittleEndian::read_i64(&data[2..])
+}
+
+fn symbol(data: &[u8]) -> String {
+    ascii(&data[10..18])
+}
+
+fn trade(data: &[u8]) -> Trade {
+    let flags = data[1];
+    Trade {
+        price: price(&data[22..]),
+        size: f64::from(LittleEndian::read_u32(&data[18..])),
+        trade_id: LittleEndian::read_i64(&data[30..]) as f64,
+        is_iso: flags & 0x80 != 0,
+        is_outside_regular_hours: flags & 0x40 != 0,
+        is_odd_lot: flags & 0x20 != 0,
+        is_trade_through_exempt: flags & 0x10 != 0,
+        is_single_price_cross: flags & 0x08 != 0,
+        timestamp: millis(timestamp(data)),
+    }
+}
+
+fn price(data: &[u8]) -> f64 {
+    LittleEndian::read_i64(data) as f64 / PRICE_SCALE
+}
+
+pub(crate) fn millis(nanos: i64) -> f64 {
+    nanos as f64 / 1_000_000.0
+}
+
+fn char_string(byte: u8) -> String {
+    (byte as char).to_string()
+}
+
+fn ascii(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).trim_end().to_string()
+}
+
+fn decode_error<S: Into<String>>(message: S) -> IexError {
+    IexError::Decode(message.into())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pcap;
+
+    pub(crate) const TIMESTAMP: i64 = 1_509_795_046_090_464_161;
+
+    /// Builds a message with the common flags, timestamp and symbol fields.
+    pub(crate) fn message(message_type: u8, flags: u8, symbol: &str, rest: &[u8]) -> Vec<u8> {
+        let mut data = vec![message_type, flags, 0, 0, 0, 0, 0, 0, 0, 0];
+        LittleEndian::write_i64(&mut data[2..], TIMESTAMP);
+        data.extend(format!("{:<8}", symbol).bytes());
+        data.extend_from_slice(rest);
+        data
+    }
+
+    pub(crate) fn le_u32(n: u32) -> Vec<u8> {
+        let mut buf = vec![0; 4];
+        LittleEndian::write_u32(&mut buf, n);
+        buf
+    }
+
+    pub(crate) fn le_price(price: f64) -> Vec<u8> {
+        let mut buf = vec![0; 8];
+        LittleEndian::write_i64(&mut buf, (price * PRICE_SCALE).round() as i64);
+        buf
+    }
+
+    pub(crate) fn segment(protocol: u16, messages: &[Vec<u8>]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for message in messages {
+            let mut len = [0; 2];
+            LittleEndian::write_u16(&mut len, message.len() as u16);
+            payload.extend_from_slice(&len);
+            payload.extend_from_slice(message);
+        }
+        let mut data = vec![0; SEGMENT_HEADER_LEN];
+        data[0] = VERSION;
+        LittleEndian::write_u16(&mut data[2..], protocol);
+        LittleEndian::write_u32(&mut data[4..], 1);
+        LittleEndian::write_u32(&mut data[8..], 1_150_681_088);
+        LittleEndian::write_u16(&mut data[12..], payload.len() as u16);
+        LittleEndian::write_u16(&mut data[14..], messages.len() as u16);
+        LittleEndian::write_i64(&mut data[16..], 0);
+        LittleEndian::write_i64(&mut data[24..], 1);
+        LittleEndian::write_i64(&mut data[32..], TIMESTAMP);
+        data.extend(payload);
+        data
+    }
+
+    fn parse(data: Vec<u8>) -> Message {
+        Message::parse(&data).unwrap()
+    }
+
+    fn trade_report(message_type: u8, flags: u8) -> Vec<u8> {
+        let mut rest = le_u32(100);
+        rest.extend(le_price(99.05));
+        rest.extend(vec![0x96, 0x8f, 0x06, 0, 0, 0, 0, 0]);
+        message(message_type, flags, "ZIEXT", &rest)
+    }
+
+    #[test]
+    fn system_event() {
+        let mut data = vec![b'S', b'R', 0, 0, 0, 0, 0, 0, 0, 0];
+        LittleEndian::write_i64(&mut data[2..], TIMESTAMP);
+        match parse(data) {
+            Message::SystemEvent(event) => {
+                assert_eq!(event.system_event, "R");
+                assert_eq!(event.timestamp.floor(), 1509795046090.0);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn security_directory() {
+        let mut rest = le_u32(100);
+        rest.extend(le_price(99.05));
+        rest.push(1);
+        match parse(message(b'D', 0x80, "ZIEXT", &rest)) {
+            Message::SecurityDirectory(directory) => {
+                assert_eq!(directory.symbol, "ZIEXT");
+                assert!(directory.is_test_security);
+                assert!(!directory.is_etp);
+                assert_eq!(directory.timestamp, TIMESTAMP);
+                assert_eq!(directory.round_lot_size, 100);
+                assert_eq!(directory.adjusted_poc_price, 99.05);
+                assert_eq!(directory.luld_tier, 1);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn statuses() {
+        match parse(message(b'H', b'H', "ZIEXT", b"T1  ")) {
+            Message::TradingStatus { symbol, status } => {
+                assert_eq!(symbol, "ZIEXT");
+                assert_eq!(status.status, "H");
+                assert_eq!(status.reason, "T1");
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+        match parse(message(b'O', b'O', "ZIEXT", &[])) {
+            Message::OperationalHalt { status, .. } => assert!(status.is_halted),
+            message => panic!("unexpected message: {:?}", message),
+        }
+        match parse(message(b'P', 1, "ZIEXT", b"A")) {
+            Message::ShortSalePriceTest { status, .. } => {
+                assert!(status.is_ssr);
+                assert_eq!(status.detail, "A");
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn quote_update() {
+        let mut rest = le_u32(9700);
+        rest.extend(le_price(99.05));
+        rest.extend(le_price(99.07));
+        rest.extend(le_u32(1000));
+        match parse(message(b'Q', 0x40, "ZIEXT", &rest)) {
+            Message::QuoteUpdate(quote) => {
+                assert_eq!(quote.symbol, "ZIEXT");
+                assert!(!quote.is_halted);
+                assert!(quote.is_pre_post_market);
+                assert_eq!(quote.bid().price, 99.05);
+                assert_eq!(quote.bid().size, 9700.0);
+                assert_eq!(quote.ask().price, 99.07);
+                assert_eq!(quote.ask().size, 1000.0);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn trade_report_and_break() {
+        match parse(trade_report(b'T', 0xa0)) {
+            Message::TradeReport { symbol, trade } => {
+                assert_eq!(symbol, "ZIEXT");
+                assert_eq!(trade.price, 99.05);
+                assert_eq!(trade.size, 100.0);
+                assert_eq!(trade.trade_id, 429974.0);
+                assert!(trade.is_iso);
+                assert!(trade.is_odd_lot);
+                assert!(!trade.is_outside_regular_hours);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+        match parse(trade_report(b'B', 0)) {
+            Message::TradeBreak { trade, .. } => assert_eq!(trade.trade_id, 429974.0),
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn official_price() {
+        match parse(message(b'X', b'Q', "ZIEXT", &le_price(1.05))) {
+            Message::OfficialPrice { price, .. } => {
+                assert_eq!(price.price_type, "Open");
+                assert_eq!(price.price, 1.05);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn price_level_update() {
+        let mut rest = le_u32(100);
+        rest.extend(le_price(99.05));
+        match parse(message(b'8', 0x01, "ZIEXT", &rest)) {
+            Message::PriceLevelUpdate(update) => {
+                assert_eq!(update.symbol, "ZIEXT");
+                assert_eq!(update.side, Side::Buy);
+                assert!(update.is_event_complete);
+                assert_eq!(update.size, 100);
+                assert_eq!(update.price, 99.05);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+        match parse(message(b'5', 0, "ZIEXT", &rest)) {
+            Message::PriceLevelUpdate(update) => {
+                assert_eq!(update.side, Side::Sell);
+                assert!(!update.is_event_complete);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn security_event() {
+        match parse(message(b'E', b'C', "ZIEXT", &[])) {
+            Message::SecurityEvent { symbol, event } => {
+                assert_eq!(symbol, "ZIEXT");
+                assert_eq!(event.security_event, "MarketClose");
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn auction_information() {
+        let mut rest = le_u32(3600);
+        rest.extend(le_price(1.05));
+        rest.extend(le_price(1.06));
+        rest.extend(le_u32(600));
+        rest.extend(vec![b'B', 2]);
+        rest.extend(le_u32(1_506_706_200));
+        rest.extend(le_price(1.07));
+        rest.extend(le_price(1.05));
+        rest.extend(le_price(0.35));
+        rest.extend(le_price(1.75));
+        match parse(message(b'A', b'C', "ZIEXT", &rest)) {
+            Message::AuctionInformation(auction) => {
+                assert_eq!(auction.auction_type, b'C');
+                assert_eq!(auction.paired_shares, 3600);
+                assert_eq!(auction.indicative_clearing_price, 1.06);
+                assert_eq!(auction.imbalance_shares, 600);
+                assert_eq!(auction.imbalance_side, b'B');
+                assert_eq!(auction.extension_number, 2);
+                assert_eq!(auction.scheduled_auction_time, 1_506_706_200);
+                assert_eq!(auction.auction_book_clearing_price, 1.07);
+                assert_eq!(auction.upper_auction_collar, 1.75);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn unknown_and_truncated_messages() {
+        match parse(vec![b'I', 1, 2, 3]) {
+            Message::Unknown { message_type, data } => {
+                assert_eq!(message_type, b'I');
+                assert_eq!(data.len(), 4);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        }
+        assert!(Message::parse(&message(b'Q', 0, "ZIEXT", &[])).is_err());
+        assert!(Message::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn segment_header() {
+        let data = segment(TOPS_PROTOCOL_ID, &[message(b'O', b'N', "ZIEXT", &[])]);
+        let segment = Segment::parse(&data).unwrap();
+        assert_eq!(segment.header.message_protocol_id, TOPS_PROTOCOL_ID);
+        assert_eq!(segment.header.session_id, 1_150_681_088);
+        assert_eq!(segment.header.payload_length, 20);
+        assert_eq!(segment.header.send_time, TIMESTAMP);
+        assert_eq!(segment.messages.len(), 1);
+
+        assert!(Segment::parse(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn hist_reader() {
+        let first = segment(
+            TOPS_PROTOCOL_ID,
+            &[message(b'O', b'N', "ZIEXT", &[]), trade_report(b'T', 0)],
+        );
+        let heartbeat = segment(TOPS_PROTOCOL_ID, &[]);
+        let second = segment(TOPS_PROTOCOL_ID, &[trade_report(b'B', 0)]);
+        let file = pcap::pcap_file(&[&first, b"not IEX-TP", &heartbeat, &second]);
+
+        let messages: Vec<Message> = HistReader::new(&file[..])
+            .unwrap()
+            .map(|message| message.unwrap())
+            .collect();
+        assert_eq!(messages.len(), 3);
+        match messages[2] {
+            Message::TradeBreak { .. } => {}
+            ref message => panic!("unexpected message: {:?}", message),
+        }
+
+        let mut reader = HistReader::new(&file[..]).unwrap();
+        assert_eq!(reader.next_segment().unwrap().unwrap().messages.len(), 2);
+        assert_eq!(reader.next_segment().unwrap().unwrap().messages.len(), 0);
+    }
+}
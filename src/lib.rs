@@ -2,21 +2,60 @@
 extern crate derive_builder;
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
 extern crate failure;
+#[macro_use]
+extern crate failure_derive;
+extern crate base64;
+extern crate byteorder;
+extern crate chrono;
+extern crate futures;
+extern crate native_tls;
+extern crate rand;
 extern crate reqwest;
 extern crate serde;
 extern crate serde_json;
+extern crate serde_urlencoded;
+extern crate tokio_core;
 
-mod types;
+#[cfg(test)]
+macro_rules! fixture {
+    ($name:expr) => {
+        include_str!(concat!("../tests/fixtures/", $name, ".json"))
+    };
+}
 
-use failure::Error;
+mod async_client;
+pub mod backoff;
+pub mod cassette;
+mod endpoints;
+mod error;
+pub mod hist;
+pub mod iextp;
+pub mod order_book;
+pub mod pcap;
+pub mod request;
+pub mod sse;
+pub mod stream;
+pub mod transport;
+pub mod types;
+mod websocket;
+
+pub use async_client::{AsyncHttpTransport, AsyncIexClient, AsyncTransport, IexFuture};
+use cassette::{RecordingTransport, ReplayTransport};
+pub use error::IexError;
+use request::Request;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::result;
 use std::time;
-use types::*;
+use tokio_core::reactor::Handle;
+use transport::{HttpTransport, Transport};
+pub use types::*;
 
-pub type Result<T> = result::Result<T, Error>;
+pub type Result<T> = result::Result<T, IexError>;
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum Duration {
     FiveYears,
     TwoYears,
@@ -26,9 +65,8 @@ pub enum Duration {
     ThreeMonths,
     OneMonth,
     OneDay,
-    // TODO: Date returns a different JSON structure to the rest of the duration parameters. We'll
-    // need to make a different function to support it.
-    // Date(&'a str),
+    // Charts for a specific date return minute bars rather than daily ones, so they have their
+    // own methods: `chart_by_date` and `chart_by_date_with_params`.
     Dynamic,
 }
 
@@ -54,7 +92,46 @@ impl Default for Duration {
     }
 }
 
-#[derive(Serialize, Debug, Builder)]
+/// The market lists available from `/stock/market/list/{list}`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarketList {
+    MostActive,
+    Gainers,
+    Losers,
+    IexVolume,
+    IexPercent,
+    InFocus,
+}
+
+impl ToString for MarketList {
+    fn to_string(&self) -> String {
+        match self {
+            MarketList::MostActive => String::from("mostactive"),
+            MarketList::Gainers => String::from("gainers"),
+            MarketList::Losers => String::from("losers"),
+            MarketList::IexVolume => String::from("iexvolume"),
+            MarketList::IexPercent => String::from("iexpercent"),
+            MarketList::InFocus => String::from("infocus"),
+        }
+    }
+}
+
+/// Parameters for the market lists. `display_percent` returns percentages multiplied by 100.
+#[derive(Serialize, Clone, Debug, Builder)]
+#[builder(setter(into))]
+#[serde(rename_all = "camelCase")]
+pub struct ListParams {
+    #[builder(default)]
+    pub display_percent: Option<bool>,
+}
+
+impl Default for ListParams {
+    fn default() -> ListParams {
+        ListParamsBuilder::default().build().unwrap()
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Builder)]
 #[builder(setter(into))]
 #[serde(rename_all = "camelCase")]
 pub struct ChartParams {
@@ -72,25 +149,308 @@ impl Default for ChartParams {
     }
 }
 
+/// The data types that can be requested in a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BatchType {
+    Book,
+    Chart,
+    Company,
+    DelayedQuote,
+    Dividends,
+    Earnings,
+    EffectiveSpread,
+    Financials,
+    Logo,
+    News,
+    Ohlc,
+    Peers,
+    Previous,
+    Price,
+    Quote,
+    Relevant,
+    Splits,
+    Stats,
+    VolumeByVenue,
+}
+
+impl ToString for BatchType {
+    fn to_string(&self) -> String {
+        match self {
+            BatchType::Book => String::from("book"),
+            BatchType::Chart => String::from("chart"),
+            BatchType::Company => String::from("company"),
+            BatchType::DelayedQuote => String::from("delayed-quote"),
+            BatchType::Dividends => String::from("dividends"),
+            BatchType::Earnings => String::from("earnings"),
+            BatchType::EffectiveSpread => String::from("effective-spread"),
+            BatchType::Financials => String::from("financials"),
+            BatchType::Logo => String::from("logo"),
+            BatchType::News => String::from("news"),
+            BatchType::Ohlc => String::from("ohlc"),
+            BatchType::Peers => String::from("peers"),
+            BatchType::Previous => String::from("previous"),
+            BatchType::Price => String::from("price"),
+            BatchType::Quote => String::from("quote"),
+            BatchType::Relevant => String::from("relevant"),
+            BatchType::Splits => String::from("splits"),
+            BatchType::Stats => String::from("stats"),
+            BatchType::VolumeByVenue => String::from("volume-by-venue"),
+        }
+    }
+}
+
+/// Parameters for the batch endpoints.
+///
+/// `symbols` is only used by `batch`; `symbol_batch` takes its symbol separately. `range` applies
+/// to the chart, dividends and splits types, and `last` is the number of news items to return.
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+pub struct BatchParams {
+    #[builder(default)]
+    pub symbols: Vec<String>,
+    pub types: Vec<BatchType>,
+    #[builder(default)]
+    pub range: Option<Duration>,
+    #[builder(default)]
+    pub chart_params: ChartParams,
+    #[builder(default)]
+    pub last: Option<u32>,
+}
+
+impl BatchParams {
+    fn query<'a>(&'a self, with_symbols: bool) -> BatchQuery<'a> {
+        fn join<T: ToString>(items: &[T]) -> String {
+            items
+                .iter()
+                .map(|item| item.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+
+        BatchQuery {
+            symbols: if with_symbols {
+                Some(join(&self.symbols))
+            } else {
+                None
+            },
+            types: join(&self.types),
+            range: self.range.as_ref().map(|range| range.to_string()),
+            last: self.last,
+            chart_params: &self.chart_params,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BatchQuery<'a> {
+    symbols: Option<String>,
+    types: String,
+    range: Option<String>,
+    last: Option<u32>,
+    #[serde(flatten)]
+    chart_params: &'a ChartParams,
+}
+
+/// Parameters for `/stats/historical/daily`.
+///
+/// `date` is either a month (`YYYYMM`) or a single day (`YYYYMMDD`), and `last` is a number of
+/// trading days, up to 90. The API returns the last trading day if neither is set.
+#[derive(Serialize, Clone, Debug, Builder)]
+#[builder(setter(into))]
+pub struct DailyStatsParams {
+    #[builder(default)]
+    pub date: Option<String>,
+    #[builder(default)]
+    pub last: Option<u32>,
+}
+
+impl Default for DailyStatsParams {
+    fn default() -> DailyStatsParams {
+        DailyStatsParamsBuilder::default().build().unwrap()
+    }
+}
+
+/// Parameters for the news endpoint.
+///
+/// `symbol` defaults to `market`, which returns news across the whole market, and `last` is the
+/// number of items to return (the API defaults to 10, and allows at most 50).
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+pub struct NewsParams {
+    #[builder(default = "String::from(\"market\")")]
+    pub symbol: String,
+    #[builder(default)]
+    pub last: Option<u32>,
+}
+
+const DEFAULT_BASE_URL: &str = "https://api.iextrading.com";
+const DEFAULT_VERSION: &str = "1.0";
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_USER_AGENT: &str = concat!("iex-rs/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Builder)]
+#[builder(setter(into), build_fn(skip))]
 pub struct IexClient {
-    http: reqwest::Client,
+    base_url: String,
+    version: String,
+    timeout: time::Duration,
+    gzip: bool,
+    user_agent: String,
+    #[builder(setter(skip))]
+    transport: Box<Transport>,
+}
+
+impl IexClientBuilder {
+    /// Builds a client that talks to the IEX API over HTTP.
+    pub fn build(&self) -> Result<IexClient> {
+        Ok(self.build_with_transport(self.http_transport()?))
+    }
+
+    /// Builds a client that talks to the IEX API over HTTP and records every request and response
+    /// to the cassette at `path`, to be played back later with `IexClient::replay`.
+    pub fn build_recording<P>(&self, path: P) -> Result<IexClient>
+    where
+        P: Into<PathBuf>,
+    {
+        let transport = RecordingTransport::new(self.http_transport()?, path);
+        Ok(self.build_with_transport(transport))
+    }
+
+    /// Builds a client that sends its requests through `transport` instead of over HTTP.
+    pub fn build_with_transport<T>(&self, transport: T) -> IexClient
+    where
+        T: Transport + 'static,
+    {
+        IexClient {
+            base_url: self.get_base_url(),
+            version: self.get_version(),
+            timeout: self.get_timeout(),
+            gzip: self.get_gzip(),
+            user_agent: self.get_user_agent(),
+            transport: Box::new(transport),
+        }
+    }
+
+    /// Builds a non-blocking client that talks to the IEX API over HTTP on the given reactor.
+    pub fn build_async(&self, handle: &Handle) -> Result<AsyncIexClient> {
+        let http = reqwest::unstable::async::Client::builder()
+            .gzip(self.get_gzip())
+            .timeout(self.get_timeout())
+            .default_headers(self.default_headers())
+            .build(handle)?;
+        Ok(AsyncIexClient::with_transport(AsyncHttpTransport::new(
+            http,
+            self.api_url(),
+        )))
+    }
+
+    fn http_transport(&self) -> Result<HttpTransport> {
+        let http = reqwest::Client::builder()
+            .gzip(self.get_gzip())
+            .timeout(self.get_timeout())
+            .default_headers(self.default_headers())
+            .build()?;
+        Ok(HttpTransport::new(http, self.api_url()))
+    }
+
+    fn default_headers(&self) -> reqwest::header::Headers {
+        let mut headers = reqwest::header::Headers::new();
+        headers.set(reqwest::header::UserAgent::new(self.get_user_agent()));
+        headers
+    }
+
+    fn api_url(&self) -> String {
+        format!(
+            "{}/{}",
+            self.get_base_url().trim_end_matches('/'),
+            self.get_version().trim_matches('/')
+        )
+    }
+
+    fn get_base_url(&self) -> String {
+        self.base_url
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_BASE_URL))
+    }
+
+    fn get_version(&self) -> String {
+        self.version
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_VERSION))
+    }
+
+    fn get_timeout(&self) -> time::Duration {
+        self.timeout
+            .unwrap_or_else(|| time::Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+    }
+
+    fn get_gzip(&self) -> bool {
+        self.gzip.unwrap_or(true)
+    }
+
+    fn get_user_agent(&self) -> String {
+        self.user_agent
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_USER_AGENT))
+    }
 }
 
 impl IexClient {
     pub fn new() -> Result<Self> {
-        Ok(IexClient {
-            http: reqwest::Client::builder()
-                .gzip(true)
-                .timeout(time::Duration::from_secs(10))
-                .build()?,
-        })
+        IexClientBuilder::default().build()
+    }
+
+    pub fn with_transport<T>(transport: T) -> Self
+    where
+        T: Transport + 'static,
+    {
+        IexClientBuilder::default().build_with_transport(transport)
+    }
+
+    /// Creates a client that serves responses from a cassette written by
+    /// `IexClientBuilder::build_recording`. Requests that aren't in the cassette fail.
+    pub fn replay<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(IexClient::with_transport(ReplayTransport::load(path)?))
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn timeout(&self) -> time::Duration {
+        self.timeout
+    }
+
+    pub fn gzip(&self) -> bool {
+        self.gzip
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// Fetches several data types for several symbols in one request, keyed by symbol.
+    pub fn batch(&self, params: BatchParams) -> Result<HashMap<String, BatchResult>> {
+        self.execute(endpoints::batch(params)?)
+    }
+
+    /// Fetches several data types for a single symbol in one request.
+    pub fn symbol_batch(&self, symbol: &str, params: BatchParams) -> Result<BatchResult> {
+        self.execute(endpoints::symbol_batch(symbol, params)?)
     }
 
     pub fn book(&self, symbol: &str) -> Result<Book> {
-        self.get(&format!("/stock/{}/book", symbol))
+        self.execute(endpoints::book(symbol))
     }
 
-    pub fn chart(&self, symbol: &str, duration: Duration) -> Result<Vec<ChartDataPoint>> {
+    pub fn chart(&self, symbol: &str, duration: Duration) -> Result<Chart> {
         self.chart_with_params(symbol, duration, ChartParams::default())
     }
 
@@ -99,46 +459,59 @@ impl IexClient {
         symbol: &str,
         duration: Duration,
         params: ChartParams,
-    ) -> Result<Vec<ChartDataPoint>> {
-        let path = format!("/stock/{}/chart/{}", symbol, duration.to_string());
-        self.get_with_params(&path, params)
+    ) -> Result<Chart> {
+        self.execute(endpoints::chart(symbol, duration, params)?)
+    }
+
+    /// Fetches the minute bars for a single trading day, where `date` is formatted as YYYYMMDD.
+    pub fn chart_by_date(&self, symbol: &str, date: &str) -> Result<Vec<MinuteBar>> {
+        self.chart_by_date_with_params(symbol, date, ChartParams::default())
+    }
+
+    pub fn chart_by_date_with_params(
+        &self,
+        symbol: &str,
+        date: &str,
+        params: ChartParams,
+    ) -> Result<Vec<MinuteBar>> {
+        self.execute(endpoints::chart_by_date(symbol, date, params)?)
     }
 
     pub fn company(&self, symbol: &str) -> Result<Company> {
-        self.get(&format!("/stock/{}/company", symbol))
+        self.execute(endpoints::company(symbol))
+    }
+
+    /// Quotes for the crypto pairs IEX covers.
+    pub fn crypto(&self) -> Result<Vec<Quote>> {
+        self.execute(endpoints::crypto())
     }
 
     pub fn delayed_quote(&self, symbol: &str) -> Result<DelayedQuote> {
-        self.get(&format!("/stock/{}/delayed-quote", symbol))
+        self.execute(endpoints::delayed_quote(symbol))
     }
 
     pub fn dividends(&self, symbol: &str, duration: Duration) -> Result<Vec<Dividend>> {
-        self.get(&format!(
-            "/stock/{}/dividends/{}",
-            symbol,
-            duration.to_string()
-        ))
+        self.execute(endpoints::dividends(symbol, duration))
     }
 
     pub fn earnings(&self, symbol: &str) -> Result<Earnings> {
-        self.get(&format!("/stock/{}/earnings", symbol))
+        self.execute(endpoints::earnings(symbol))
     }
 
     pub fn effective_spread(&self, symbol: &str) -> Result<Vec<EffectiveSpread>> {
-        self.get(&format!("/stock/{}/effective-spread", symbol))
+        self.execute(endpoints::effective_spread(symbol))
     }
 
     pub fn financials(&self, symbol: &str) -> Result<Financials> {
-        self.get(&format!("/stock/{}/financials", symbol))
+        self.execute(endpoints::financials(symbol))
     }
 
     pub fn iex_regulation_sho_threshold_securities_list(
         &self,
         date: Option<&str>,
     ) -> Result<Vec<IEXRegulationSHOThresholdSecurity>> {
-        self.get(&format!(
-            "/stock/market/threshold-securities/{}",
-            date.unwrap_or("")
+        self.execute(endpoints::iex_regulation_sho_threshold_securities_list(
+            date,
         ))
     }
 
@@ -147,66 +520,79 @@ impl IexClient {
         symbol: Option<&str>,
         date: Option<&str>,
     ) -> Result<Vec<IEXShortInterest>> {
-        self.get(&format!(
-            "/stock/{}/short-interest/{}",
-            symbol.unwrap_or("market"),
-            date.unwrap_or("")
-        ))
+        self.execute(endpoints::iex_short_interest_list(symbol, date))
     }
 
     pub fn stats(&self, symbol: &str) -> Result<Stats> {
-        self.get(&format!("/stock/{}/stats", symbol))
+        self.execute(endpoints::stats(symbol))
+    }
+
+    pub fn list(&self, list: MarketList) -> Result<Vec<Quote>> {
+        self.list_with_params(list, ListParams::default())
     }
 
-    pub fn list(&self, list: &str) -> Result<Vec<Quote>> {
-        self.get(&format!("/stock/market/list/{}", list))
+    pub fn list_with_params(&self, list: MarketList, params: ListParams) -> Result<Vec<Quote>> {
+        self.execute(endpoints::list(list, params)?)
+    }
+
+    /// Volume across every US market, by venue.
+    pub fn market(&self) -> Result<Vec<MarketVolume>> {
+        self.execute(endpoints::market())
     }
 
     pub fn logo(&self, symbol: &str) -> Result<Logo> {
-        self.get(&format!("/stock/{}/logo", symbol))
+        self.execute(endpoints::logo(symbol))
     }
 
     pub fn news(&self, symbol: &str) -> Result<Vec<News>> {
-        // TODO: this also takes a count argument, implement it.
-        self.get(&format!("/stock/{}/news", symbol))
+        self.news_with_params(NewsParams {
+            symbol: String::from(symbol),
+            last: None,
+        })
+    }
+
+    pub fn news_with_params(&self, params: NewsParams) -> Result<Vec<News>> {
+        self.execute(endpoints::news(params))
     }
 
     pub fn ohlc(&self, symbol: &str) -> Result<OHLC> {
-        self.get(&format!("/stock/{}/ohlc", symbol))
+        self.execute(endpoints::ohlc(symbol))
     }
 
     pub fn peers(&self, symbol: &str) -> Result<Vec<String>> {
-        self.get(&format!("/stock/{}/peers", symbol))
+        self.execute(endpoints::peers(symbol))
     }
 
     pub fn previous(&self, symbol: &str) -> Result<Previous> {
-        // TODO: It's possible to pass in "market" as an argument here
-        // and get one entry for each symbol. We need to handle that
-        // scenario.
-        self.get(&format!("/stock/{}/previous", symbol))
+        self.execute(endpoints::previous(symbol))
+    }
+
+    /// The previous day's data for every symbol, keyed by symbol.
+    pub fn previous_market(&self) -> Result<HashMap<String, Previous>> {
+        self.execute(endpoints::previous_market())
     }
 
     pub fn price(&self, symbol: &str) -> Result<f64> {
-        self.get(&format!("/stock/{}/price", symbol))
+        self.execute(endpoints::price(symbol))
     }
 
     pub fn quote(&self, symbol: &str) -> Result<Quote> {
-        self.get(&format!("/stock/{}/quote", symbol))
+        self.execute(endpoints::quote(symbol))
     }
 
     pub fn relevant(&self, symbol: &str) -> Result<Relevant> {
-        self.get(&format!("/stock/{}/relevant", symbol))
+        self.execute(endpoints::relevant(symbol))
+    }
+
+    pub fn sector_performance(&self) -> Result<Vec<SectorPerformance>> {
+        self.execute(endpoints::sector_performance())
     }
 
     pub fn splits(&self, symbol: &str, duration: Duration) -> Result<Vec<Split>> {
-        self.get(&format!(
-            "/stock/{}/splits/{}",
-            symbol,
-            duration.to_string()
-        ))
+        self.execute(endpoints::splits(symbol, duration))
     }
 
-    pub fn time_series(&self, symbol: &str, duration: Duration) -> Result<Vec<ChartDataPoint>> {
+    pub fn time_series(&self, symbol: &str, duration: Duration) -> Result<Chart> {
         self.time_series_with_params(symbol, duration, ChartParams::default())
     }
 
@@ -215,186 +601,980 @@ impl IexClient {
         symbol: &str,
         duration: Duration,
         params: ChartParams,
-    ) -> Result<Vec<ChartDataPoint>> {
+    ) -> Result<Chart> {
         self.chart_with_params(symbol, duration, params)
     }
 
+    /// IEX's top of book for `symbols`, or for every symbol if `symbols` is empty.
+    pub fn tops(&self, symbols: &[&str]) -> Result<Vec<Tops>> {
+        self.execute(endpoints::tops(symbols)?)
+    }
+
+    /// The last sale on IEX for `symbols`, or for every symbol if `symbols` is empty.
+    pub fn last(&self, symbols: &[&str]) -> Result<Vec<Last>> {
+        self.execute(endpoints::last(symbols)?)
+    }
+
+    /// IEX's depth of book, trades and status for a single symbol.
+    pub fn deep(&self, symbol: &str) -> Result<Deep> {
+        self.execute(endpoints::deep(symbol)?)
+    }
+
+    // The rest of the DEEP endpoints are keyed by symbol, and cover every symbol if `symbols` is
+    // empty.
+    pub fn deep_book(&self, symbols: &[&str]) -> Result<HashMap<String, DeepBook>> {
+        self.execute(endpoints::deep_book(symbols)?)
+    }
+
+    pub fn deep_trades(&self, symbols: &[&str]) -> Result<HashMap<String, Vec<Trade>>> {
+        self.execute(endpoints::deep_trades(symbols)?)
+    }
+
+    pub fn deep_system_event(&self) -> Result<SystemEvent> {
+        self.execute(endpoints::deep_system_event())
+    }
+
+    pub fn deep_trading_status(&self, symbols: &[&str]) -> Result<HashMap<String, TradingStatus>> {
+        self.execute(endpoints::deep_trading_status(symbols)?)
+    }
+
+    pub fn deep_op_halt_status(&self, symbols: &[&str]) -> Result<HashMap<String, OpHaltStatus>> {
+        self.execute(endpoints::deep_op_halt_status(symbols)?)
+    }
+
+    pub fn deep_ssr_status(&self, symbols: &[&str]) -> Result<HashMap<String, SSRStatus>> {
+        self.execute(endpoints::deep_ssr_status(symbols)?)
+    }
+
+    pub fn deep_security_event(&self, symbols: &[&str]) -> Result<HashMap<String, SecurityEvent>> {
+        self.execute(endpoints::deep_security_event(symbols)?)
+    }
+
+    pub fn deep_trade_breaks(&self, symbols: &[&str]) -> Result<HashMap<String, Vec<Trade>>> {
+        self.execute(endpoints::deep_trade_breaks(symbols)?)
+    }
+
+    pub fn deep_auction(&self, symbols: &[&str]) -> Result<HashMap<String, Auction>> {
+        self.execute(endpoints::deep_auction(symbols)?)
+    }
+
+    pub fn deep_official_price(&self, symbols: &[&str]) -> Result<HashMap<String, OfficialPrice>> {
+        self.execute(endpoints::deep_official_price(symbols)?)
+    }
+
+    pub fn stats_intraday(&self) -> Result<IntradayStats> {
+        self.execute(endpoints::stats_intraday())
+    }
+
+    pub fn stats_recent(&self) -> Result<Vec<DailyStats>> {
+        self.execute(endpoints::stats_recent())
+    }
+
+    pub fn stats_records(&self) -> Result<RecordStats> {
+        self.execute(endpoints::stats_records())
+    }
+
+    /// Monthly stats for the month `date` (`YYYYMM`), or for every month if `date` is `None`.
+    pub fn stats_historical(&self, date: Option<&str>) -> Result<Vec<HistoricalStats>> {
+        self.execute(endpoints::stats_historical(date)?)
+    }
+
+    pub fn stats_historical_daily(&self, params: DailyStatsParams) -> Result<Vec<DailyStats>> {
+        self.execute(endpoints::stats_historical_daily(params)?)
+    }
+
     pub fn volume_by_venue(&self, symbol: &str) -> Result<Vec<VolumeByVenue>> {
-        self.get(&format!("/stock/{}/volume-by-venue", symbol))
+        self.execute(endpoints::volume_by_venue(symbol))
     }
 
     pub fn symbols(&self) -> Result<Vec<Symbol>> {
-        self.get("/ref-data/symbols")
+        self.execute(endpoints::symbols())
     }
 
-    fn get<R>(&self, path: &str) -> Result<R>
-    where
-        R: serde::de::DeserializeOwned,
-    {
-        self.get_with_params(path, HashMap::<String, String>::default())
+    /// The symbols IEX lists itself.
+    pub fn iex_symbols(&self) -> Result<Vec<Symbol>> {
+        self.execute(endpoints::iex_symbols())
+    }
+
+    pub fn otc_symbols(&self) -> Result<Vec<Symbol>> {
+        self.execute(endpoints::otc_symbols())
+    }
+
+    pub fn mutual_fund_symbols(&self) -> Result<Vec<Symbol>> {
+        self.execute(endpoints::mutual_fund_symbols())
+    }
+
+    /// The symbols for a region, given as a two letter country code such as `ca`.
+    pub fn international_symbols(&self, region: &str) -> Result<Vec<Symbol>> {
+        self.execute(endpoints::international_symbols(region))
+    }
+
+    // The daily-list endpoints take a `YYYYMMDD` date, or `sample` for sample data, and default to
+    // the latest list.
+    pub fn corporate_actions(&self, date: Option<&str>) -> Result<Vec<CorporateAction>> {
+        self.execute(endpoints::corporate_actions(date))
+    }
+
+    pub fn daily_list_dividends(&self, date: Option<&str>) -> Result<Vec<DailyListDividend>> {
+        self.execute(endpoints::daily_list_dividends(date))
+    }
+
+    pub fn next_day_ex_date(&self, date: Option<&str>) -> Result<Vec<NextDayExDate>> {
+        self.execute(endpoints::next_day_ex_date(date))
     }
 
-    fn get_with_params<R, P>(&self, path: &str, params: P) -> Result<R>
+    pub fn symbol_directory(&self, date: Option<&str>) -> Result<Vec<SymbolDirectoryEntry>> {
+        self.execute(endpoints::symbol_directory(date))
+    }
+
+    /// Every HIST capture IEX has available, keyed by `YYYYMMDD` date. Use `hist::HistDownloader`
+    /// to fetch them.
+    pub fn hist(&self) -> Result<HashMap<String, Vec<HistFile>>> {
+        self.execute(endpoints::hist())
+    }
+
+    /// The HIST captures for one `YYYYMMDD` date.
+    pub fn hist_by_date(&self, date: &str) -> Result<Vec<HistFile>> {
+        self.execute(endpoints::hist_by_date(date)?)
+    }
+
+    fn execute<R>(&self, request: Request<R>) -> Result<R>
     where
         R: serde::de::DeserializeOwned,
-        P: serde::ser::Serialize,
     {
-        let uri = format!("{}{}", "https://api.iextrading.com/1.0", path);
-        let res = self.http
-            .get(&uri)
-            .query(&params)
-            .send()?
-            .error_for_status()?;
-        Ok(serde_json::from_reader(res)?)
+        let res = self.transport.get(&request.path, &request.query)?;
+        request.parse(res)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use transport::MemoryTransport;
+
+    fn client(path: &str, query: &str, body: &str) -> IexClient {
+        IexClient::with_transport(MemoryTransport::new().with_json(path, query, body))
+    }
+
+    #[test]
+    fn builder_settings() {
+        let iex = IexClientBuilder::default()
+            .base_url("http://localhost:8080/")
+            .version("beta")
+            .timeout(time::Duration::from_secs(1))
+            .gzip(false)
+            .user_agent("iex-test")
+            .build()
+            .unwrap();
+        assert_eq!(iex.base_url(), "http://localhost:8080/");
+        assert_eq!(iex.version(), "beta");
+        assert_eq!(iex.timeout(), time::Duration::from_secs(1));
+        assert!(!iex.gzip());
+        assert_eq!(iex.user_agent(), "iex-test");
+    }
+
+    #[test]
+    fn default_client_url() {
+        let api_url = IexClientBuilder::default().api_url();
+        assert_eq!(
+            transport::url(&api_url, "/stock/aapl/quote", ""),
+            "https://api.iextrading.com/1.0/stock/aapl/quote"
+        );
+    }
+
+    #[test]
+    fn builder_client_url() {
+        let api_url = IexClientBuilder::default()
+            .base_url("http://localhost:8080/")
+            .version("/beta/")
+            .api_url();
+        assert_eq!(api_url, "http://localhost:8080/beta");
+        assert_eq!(
+            transport::url(&api_url, "/stock/aapl/quote", ""),
+            "http://localhost:8080/beta/stock/aapl/quote"
+        );
+    }
+
+    #[test]
+    fn replay() {
+        let iex = IexClient::replay("tests/fixtures/cassette.json").unwrap();
+        assert_eq!(iex.price("aapl").unwrap(), 143.28);
+        assert_eq!(
+            iex.chart_with_params(
+                "aapl",
+                Duration::OneMonth,
+                ChartParamsBuilder::default()
+                    .chart_simplify(true)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap()
+            .daily()
+            .unwrap()
+            .len(),
+            1
+        );
+        assert!(iex.price("msft").is_err());
+    }
+
+    #[test]
+    fn unmatched_request() {
+        let iex = client("/stock/aapl/quote", "", fixture!("quote"));
+        match iex.quote("msft") {
+            Err(IexError::Transport(err)) => {
+                assert_eq!(err.to_string(), "no canned response for /stock/msft/quote?")
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn mismatched_response() {
+        let iex = client("/stock/aapl/quote", "", fixture!("company"));
+        match iex.quote("aapl") {
+            Err(IexError::Deserialize { path, .. }) => assert_eq!(path, "/stock/aapl/quote"),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn batch() {
+        let iex = client(
+            "/stock/market/batch",
+            "symbols=aapl%2Cfb&types=quote%2Cnews%2Cchart&range=1m&last=5&chartSimplify=true",
+            fixture!("batch"),
+        );
+        let params = BatchParamsBuilder::default()
+            .symbols(vec![String::from("aapl"), String::from("fb")])
+            .types(vec![BatchType::Quote, BatchType::News, BatchType::Chart])
+            .range(Duration::OneMonth)
+            .chart_params(
+                ChartParamsBuilder::default()
+                    .chart_simplify(true)
+                    .build()
+                    .unwrap(),
+            )
+            .last(5)
+            .build()
+            .unwrap();
+        let batch = iex.batch(params).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch["AAPL"].quote.is_some());
+    }
+
+    #[test]
+    fn symbol_batch() {
+        let iex = client(
+            "/stock/aapl/batch",
+            "types=quote%2Cprice",
+            fixture!("batch_symbol"),
+        );
+        let params = BatchParamsBuilder::default()
+            .types(vec![BatchType::Quote, BatchType::Price])
+            .build()
+            .unwrap();
+        let batch = iex.symbol_batch("aapl", params).unwrap();
+        assert_eq!(batch.price, Some(143.28));
+        assert_eq!(batch.quote.unwrap().symbol, "AAPL");
+    }
+
+    #[test]
+    fn batch_requires_types() {
+        assert!(BatchParamsBuilder::default().build().is_err());
+    }
+
     #[test]
     fn book() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/book", "", fixture!("book"));
         assert!(iex.book("aapl").is_ok());
     }
 
     #[test]
     fn chart() {
-        let iex = ::IexClient::new().unwrap();
-        assert!(iex.chart("aapl", ::Duration::default()).is_ok());
+        let iex = client("/stock/aapl/chart/1m", "", fixture!("chart"));
+        assert!(iex.chart("aapl", Duration::default()).is_ok());
+    }
+
+    #[test]
+    fn chart_one_day() {
+        let iex = client("/stock/aapl/chart/1d", "", fixture!("chart_1d"));
+        let chart = iex.chart("aapl", Duration::OneDay).unwrap();
+        assert_eq!(chart.intraday().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn chart_dynamic() {
+        let iex = client("/stock/aapl/chart/dynamic", "", fixture!("chart_dynamic"));
+        match iex.chart("aapl", Duration::Dynamic).unwrap() {
+            Chart::Dynamic { range, .. } => assert_eq!(range, "1d"),
+            chart => panic!("unexpected chart: {:?}", chart),
+        }
+    }
+
+    #[test]
+    fn chart_with_params() {
+        let iex = client(
+            "/stock/aapl/chart/ytd",
+            "chartSimplify=true&chartInterval=5",
+            fixture!("chart"),
+        );
+        assert!(iex
+            .chart_with_params(
+                "aapl",
+                Duration::YearToDate,
+                ChartParamsBuilder::default()
+                    .chart_simplify(true)
+                    .chart_interval(5)
+                    .build()
+                    .unwrap(),
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn chart_by_date() {
+        let iex = client(
+            "/stock/aapl/chart/date/20171215",
+            "",
+            fixture!("chart_date"),
+        );
+        assert!(iex.chart_by_date("aapl", "20171215").is_ok());
+    }
+
+    #[test]
+    fn chart_by_date_with_params() {
+        let iex = client(
+            "/stock/aapl/chart/date/20171215",
+            "chartInterval=10",
+            fixture!("chart_date"),
+        );
+        assert!(iex
+            .chart_by_date_with_params(
+                "aapl",
+                "20171215",
+                ChartParamsBuilder::default()
+                    .chart_interval(10)
+                    .build()
+                    .unwrap(),
+            )
+            .is_ok());
     }
 
     #[test]
     fn company() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/company", "", fixture!("company"));
         assert!(iex.company("aapl").is_ok());
     }
 
+    #[test]
+    fn crypto() {
+        let iex = client("/stock/market/crypto", "", fixture!("crypto"));
+        assert_eq!(iex.crypto().unwrap().len(), 2);
+    }
+
     #[test]
     fn delayed_quote() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/delayed-quote", "", fixture!("delayed_quote"));
         assert!(iex.delayed_quote("aapl").is_ok());
     }
 
     #[test]
     fn dividends() {
-        let iex = ::IexClient::new().unwrap();
-        assert!(iex.dividends("aapl", ::Duration::default()).is_ok());
+        let iex = client("/stock/aapl/dividends/1m", "", fixture!("dividends"));
+        assert!(iex.dividends("aapl", Duration::default()).is_ok());
     }
 
     #[test]
     fn earnings() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/earnings", "", fixture!("earnings"));
         assert!(iex.earnings("aapl").is_ok());
     }
 
     #[test]
     fn effective_spread() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client(
+            "/stock/aapl/effective-spread",
+            "",
+            fixture!("effective_spread"),
+        );
         assert!(iex.effective_spread("aapl").is_ok());
     }
 
     #[test]
     fn financials() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/financials", "", fixture!("financials"));
         assert!(iex.financials("aapl").is_ok());
     }
 
     #[test]
     fn iex_regulation_sho_threshold_securities_list() {
-        let iex = ::IexClient::new().unwrap();
-        assert!(
-            iex.iex_regulation_sho_threshold_securities_list(Some("sample"))
-                .is_ok()
+        let iex = client(
+            "/stock/market/threshold-securities/sample",
+            "",
+            fixture!("threshold_securities"),
         );
+        assert!(iex
+            .iex_regulation_sho_threshold_securities_list(Some("sample"))
+            .is_ok());
     }
 
     #[test]
     fn iex_short_interest_list() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client(
+            "/stock/market/short-interest/",
+            "",
+            fixture!("short_interest"),
+        );
         assert!(iex.iex_short_interest_list(None, None).is_ok());
     }
 
     #[test]
     fn stats() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/stats", "", fixture!("stats"));
         assert!(iex.stats("aapl").is_ok());
     }
 
     #[test]
     fn list() {
-        let iex = ::IexClient::new().unwrap();
-        assert!(iex.list("gainers").is_ok());
+        let iex = client("/stock/market/list/gainers", "", fixture!("list"));
+        assert!(iex.list(MarketList::Gainers).is_ok());
+    }
+
+    #[test]
+    fn list_with_params() {
+        let iex = client(
+            "/stock/market/list/infocus",
+            "displayPercent=true",
+            fixture!("list"),
+        );
+        let params = ListParamsBuilder::default()
+            .display_percent(true)
+            .build()
+            .unwrap();
+        assert!(iex.list_with_params(MarketList::InFocus, params).is_ok());
+    }
+
+    #[test]
+    fn market() {
+        let iex = client("/market", "", fixture!("market"));
+        assert!(iex.market().is_ok());
+    }
+
+    #[test]
+    fn sector_performance() {
+        let iex = client(
+            "/stock/market/sector-performance",
+            "",
+            fixture!("sector_performance"),
+        );
+        assert!(iex.sector_performance().is_ok());
     }
 
     #[test]
     fn logo() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/logo", "", fixture!("logo"));
         assert!(iex.logo("aapl").is_ok());
     }
 
     #[test]
     fn news() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/news", "", fixture!("news"));
         assert!(iex.news("aapl").is_ok());
     }
 
+    #[test]
+    fn news_with_params() {
+        let iex = client("/stock/market/news/last/2", "", fixture!("news_market"));
+        let params = NewsParamsBuilder::default().last(2).build().unwrap();
+        assert_eq!(iex.news_with_params(params).unwrap().len(), 2);
+    }
+
     #[test]
     fn ohlc() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/ohlc", "", fixture!("ohlc"));
         assert!(iex.ohlc("aapl").is_ok());
     }
 
     #[test]
     fn peers() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/peers", "", fixture!("peers"));
         assert!(iex.peers("aapl").is_ok());
     }
 
     #[test]
     fn previous() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/previous", "", fixture!("previous"));
         assert!(iex.previous("aapl").is_ok());
     }
 
+    #[test]
+    fn previous_market() {
+        let iex = client("/stock/market/previous", "", fixture!("previous_market"));
+        let previous = iex.previous_market().unwrap();
+        assert_eq!(previous.len(), 2);
+        assert_eq!(previous["MSFT"].close, 75.44);
+    }
+
     #[test]
     fn price() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/price", "", fixture!("price"));
         assert!(iex.price("aapl").is_ok());
     }
 
     #[test]
     fn quote() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/quote", "", fixture!("quote"));
         assert!(iex.quote("aapl").is_ok());
     }
 
     #[test]
     fn relevant() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/stock/aapl/relevant", "", fixture!("relevant"));
         assert!(iex.relevant("aapl").is_ok());
     }
 
     #[test]
     fn splits() {
-        let iex = ::IexClient::new().unwrap();
-        assert!(iex.splits("aapl", ::Duration::default()).is_ok());
+        let iex = client("/stock/aapl/splits/1m", "", fixture!("splits"));
+        assert!(iex.splits("aapl", Duration::default()).is_ok());
     }
 
     #[test]
     fn time_series() {
-        let iex = ::IexClient::new().unwrap();
-        assert!(iex.time_series("aapl", ::Duration::default()).is_ok());
+        let iex = client("/stock/aapl/chart/1m", "", fixture!("chart"));
+        assert!(iex.time_series("aapl", Duration::default()).is_ok());
+    }
+
+    #[test]
+    fn corporate_actions() {
+        let iex = client(
+            "/ref-data/daily-list/corporate-actions/20171108",
+            "",
+            fixture!("corporate_actions"),
+        );
+        assert!(iex.corporate_actions(Some("20171108")).is_ok());
+    }
+
+    #[test]
+    fn daily_list_dividends() {
+        let iex = client(
+            "/ref-data/daily-list/dividends/20171108",
+            "",
+            fixture!("daily_list_dividends"),
+        );
+        assert!(iex.daily_list_dividends(Some("20171108")).is_ok());
+    }
+
+    #[test]
+    fn next_day_ex_date() {
+        let iex = client(
+            "/ref-data/daily-list/next-day-ex-date/20171108",
+            "",
+            fixture!("next_day_ex_date"),
+        );
+        assert!(iex.next_day_ex_date(Some("20171108")).is_ok());
+    }
+
+    #[test]
+    fn symbol_directory() {
+        let iex = client(
+            "/ref-data/daily-list/symbol-directory/20171108",
+            "",
+            fixture!("symbol_directory"),
+        );
+        assert!(iex.symbol_directory(Some("20171108")).is_ok());
+    }
+
+    #[test]
+    fn tops() {
+        let iex = client("/tops", "symbols=snap%2Cfb", fixture!("tops"));
+        assert_eq!(iex.tops(&["snap", "fb"]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn tops_all_symbols() {
+        let iex = client("/tops", "", fixture!("tops"));
+        assert!(iex.tops(&[]).is_ok());
+    }
+
+    #[test]
+    fn last() {
+        let iex = client("/tops/last", "symbols=snap", fixture!("last"));
+        assert!(iex.last(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep() {
+        let iex = client("/deep", "symbols=snap", fixture!("deep"));
+        assert!(iex.deep("snap").is_ok());
+    }
+
+    #[test]
+    fn deep_system_event() {
+        let iex = client("/deep/system-event", "", fixture!("deep_system_event"));
+        assert_eq!(iex.deep_system_event().unwrap().system_event, "C");
+    }
+
+    #[test]
+    fn deep_book() {
+        let iex = client("/deep/book", "symbols=snap", fixture!("deep_book"));
+        assert!(iex.deep_book(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_trades() {
+        let iex = client("/deep/trades", "symbols=snap", fixture!("deep_trades"));
+        assert!(iex.deep_trades(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_trading_status() {
+        let iex = client(
+            "/deep/trading-status",
+            "symbols=snap",
+            fixture!("deep_trading_status"),
+        );
+        assert!(iex.deep_trading_status(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_op_halt_status() {
+        let iex = client(
+            "/deep/op-halt-status",
+            "symbols=snap",
+            fixture!("deep_op_halt_status"),
+        );
+        assert!(iex.deep_op_halt_status(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_ssr_status() {
+        let iex = client(
+            "/deep/ssr-status",
+            "symbols=snap",
+            fixture!("deep_ssr_status"),
+        );
+        assert!(iex.deep_ssr_status(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_security_event() {
+        let iex = client(
+            "/deep/security-event",
+            "symbols=snap",
+            fixture!("deep_security_event"),
+        );
+        assert!(iex.deep_security_event(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_trade_breaks() {
+        let iex = client(
+            "/deep/trade-breaks",
+            "symbols=snap",
+            fixture!("deep_trade_breaks"),
+        );
+        assert!(iex.deep_trade_breaks(&["snap"]).is_ok());
+    }
+
+    #[test]
+    fn deep_auction() {
+        let iex = client("/deep/auction", "symbols=ziext", fixture!("deep_auction"));
+        assert!(iex.deep_auction(&["ziext"]).is_ok());
+    }
+
+    #[test]
+    fn deep_official_price() {
+        let iex = client(
+            "/deep/official-price",
+            "symbols=ziext",
+            fixture!("deep_official_price"),
+        );
+        assert!(iex.deep_official_price(&["ziext"]).is_ok());
+    }
+
+    #[test]
+    fn stats_intraday() {
+        let iex = client("/stats/intraday", "", fixture!("stats_intraday"));
+        assert!(iex.stats_intraday().is_ok());
+    }
+
+    #[test]
+    fn stats_recent() {
+        let iex = client("/stats/recent", "", fixture!("stats_recent"));
+        assert!(iex.stats_recent().is_ok());
+    }
+
+    #[test]
+    fn stats_records() {
+        let iex = client("/stats/records", "", fixture!("stats_records"));
+        assert!(iex.stats_records().is_ok());
+    }
+
+    #[test]
+    fn stats_historical() {
+        let iex = client(
+            "/stats/historical",
+            "date=201605",
+            fixture!("stats_historical"),
+        );
+        assert!(iex.stats_historical(Some("201605")).is_ok());
+
+        let iex = client("/stats/historical", "", fixture!("stats_historical"));
+        assert!(iex.stats_historical(None).is_ok());
+    }
+
+    #[test]
+    fn stats_historical_daily() {
+        let iex = client(
+            "/stats/historical/daily",
+            "last=2",
+            fixture!("stats_historical_daily"),
+        );
+        let params = DailyStatsParamsBuilder::default().last(2).build().unwrap();
+        assert_eq!(iex.stats_historical_daily(params).unwrap().len(), 2);
     }
 
     #[test]
     fn volume_by_venue() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client(
+            "/stock/aapl/volume-by-venue",
+            "",
+            fixture!("volume_by_venue"),
+        );
         assert!(iex.volume_by_venue("aapl").is_ok());
     }
 
     #[test]
     fn symbols() {
-        let iex = ::IexClient::new().unwrap();
+        let iex = client("/ref-data/symbols", "", fixture!("symbols"));
         assert!(iex.symbols().is_ok());
     }
+
+    #[test]
+    fn iex_symbols() {
+        let iex = client("/ref-data/iex/symbols", "", fixture!("iex_symbols"));
+        assert!(iex.iex_symbols().is_ok());
+    }
+
+    #[test]
+    fn otc_symbols() {
+        let iex = client("/ref-data/otc/symbols", "", fixture!("otc_symbols"));
+        assert!(iex.otc_symbols().is_ok());
+    }
+
+    #[test]
+    fn mutual_fund_symbols() {
+        let iex = client(
+            "/ref-data/mutual-funds/symbols",
+            "",
+            fixture!("mutual_fund_symbols"),
+        );
+        let funds = iex.mutual_fund_symbols().unwrap();
+        assert_eq!(funds.len(), 2);
+        assert_eq!(funds[0].symbol, "VFIAX");
+        assert_eq!(funds[0].exchange, Some(String::from("MUTUAL")));
+        assert!(funds
+            .iter()
+            .all(|fund| fund.symbol_type == Some(SymbolType::OpenEndFund)));
+    }
+
+    #[test]
+    fn international_symbols() {
+        let iex = client(
+            "/ref-data/region/ca/symbols",
+            "",
+            fixture!("international_symbols"),
+        );
+        assert!(iex.international_symbols("ca").is_ok());
+    }
+
+    #[test]
+    fn hist() {
+        let iex = client("/hist", "", fixture!("hist"));
+        assert_eq!(iex.hist().unwrap().len(), 2);
+
+        let iex = client("/hist", "date=20180127", fixture!("hist_date"));
+        assert_eq!(iex.hist_by_date("20180127").unwrap().len(), 2);
+    }
+
+    // These talk to the real IEX API. Run them with `cargo test -- --ignored`.
+    mod live {
+        #[test]
+        #[ignore]
+        fn book() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.book("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn chart() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.chart("aapl", ::Duration::default()).is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn company() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.company("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn delayed_quote() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.delayed_quote("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn dividends() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.dividends("aapl", ::Duration::default()).is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn earnings() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.earnings("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn effective_spread() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.effective_spread("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn financials() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.financials("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn iex_regulation_sho_threshold_securities_list() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex
+                .iex_regulation_sho_threshold_securities_list(Some("sample"))
+                .is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn iex_short_interest_list() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.iex_short_interest_list(None, None).is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn stats() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.stats("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn list() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.list(::MarketList::Gainers).is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn logo() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.logo("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn news() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.news("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn ohlc() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.ohlc("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn peers() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.peers("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn previous() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.previous("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn price() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.price("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn quote() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.quote("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn relevant() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.relevant("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn splits() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.splits("aapl", ::Duration::default()).is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn time_series() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.time_series("aapl", ::Duration::default()).is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn volume_by_venue() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.volume_by_venue("aapl").is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn symbols() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.symbols().is_ok());
+        }
+
+        #[test]
+        #[ignore]
+        fn hist() {
+            let iex = ::IexClient::new().unwrap();
+            assert!(iex.hist().is_ok());
+        }
+    }
 }
@@ -0,0 +1,345 @@
+//! Rebuilding IEX's order book from the price level updates on the DEEP feed.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use iextp::{self, HistReader, Message, PriceLevelUpdate, Side};
+use types::{Ask, Bid, Trade};
+use Result;
+
+const DEFAULT_MAX_TRADES: usize = 20;
+
+/// One symbol's order book at a point in time, in the same shape as `Book`: bids and asks best
+/// first, and trades newest first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookSnapshot {
+    pub symbol: String,
+    /// When the book last changed, in milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    pub bids: Vec<Bid>,
+    pub asks: Vec<Ask>,
+    pub trades: Vec<Trade>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Level {
+    size: u32,
+    timestamp: f64,
+}
+
+// Price levels are keyed by IEX's fixed point price, since `f64` isn't `Ord`.
+#[derive(Debug, Default)]
+struct SymbolBook {
+    bids: BTreeMap<i64, Level>,
+    asks: BTreeMap<i64, Level>,
+    pending: Vec<PriceLevelUpdate>,
+    trades: VecDeque<Trade>,
+    timestamp: f64,
+}
+
+impl SymbolBook {
+    fn apply(&mut self, update: &PriceLevelUpdate) {
+        let levels = match update.side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let key = (update.price * iextp::PRICE_SCALE).round() as i64;
+        if update.size == 0 {
+            levels.remove(&key);
+        } else {
+            levels.insert(
+                key,
+                Level {
+                    size: update.size,
+                    timestamp: iextp::millis(update.timestamp),
+                },
+            );
+        }
+    }
+}
+
+/// Maintains the order book for every symbol on a DEEP feed, as messages are applied in order.
+///
+/// Price level updates are held back until IEX marks the event they belong to as complete, so
+/// snapshots never show a book in the middle of changing.
+#[derive(Debug)]
+pub struct BookBuilder {
+    books: HashMap<String, SymbolBook>,
+    max_trades: usize,
+}
+
+impl BookBuilder {
+    pub fn new() -> BookBuilder {
+        BookBuilder::with_max_trades(DEFAULT_MAX_TRADES)
+    }
+
+    /// Builds books that keep the last `max_trades` trades for each symbol.
+    pub fn with_max_trades(max_trades: usize) -> BookBuilder {
+        BookBuilder {
+            books: HashMap::new(),
+            max_trades: max_trades,
+        }
+    }
+
+    /// Updates the books with `message`. Messages that don't affect the book are ignored.
+    pub fn apply(&mut self, message: &Message) {
+        let max_trades = self.max_trades;
+        match message {
+            Message::PriceLevelUpdate(update) => {
+                let book = self.book(&update.symbol);
+                book.pending.push(update.clone());
+                if update.is_event_complete {
+                    let pending: Vec<_> = book.pending.drain(..).collect();
+                    for update in &pending {
+                        book.apply(update);
+                    }
+                    book.timestamp = iextp::millis(update.timestamp);
+                }
+            }
+            Message::TradeReport { symbol, trade } => {
+                let book = self.book(symbol);
+                book.trades.push_front(trade.clone());
+                book.trades.truncate(max_trades);
+                book.timestamp = trade.timestamp;
+            }
+            Message::TradeBreak { symbol, trade } => {
+                let book = self.book(symbol);
+                book.trades.retain(|t| t.trade_id != trade.trade_id);
+                book.timestamp = trade.timestamp;
+            }
+            _ => {}
+        }
+    }
+
+    /// The symbols that have had at least one update or trade.
+    pub fn symbols(&self) -> Vec<&str> {
+        self.books.keys().map(|symbol| symbol.as_str()).collect()
+    }
+
+    pub fn snapshot(&self, symbol: &str) -> Option<BookSnapshot> {
+        let book = self.books.get(symbol)?;
+        let price = |key: &i64| *key as f64 / iextp::PRICE_SCALE;
+        Some(BookSnapshot {
+            symbol: String::from(symbol),
+            timestamp: book.timestamp,
+            bids: book
+                .bids
+                .iter()
+                .rev()
+                .map(|(key, level)| Bid {
+                    price: price(key),
+                    size: f64::from(level.size),
+                    timestamp: level.timestamp,
+                })
+                .collect(),
+            asks: book
+                .asks
+                .iter()
+                .map(|(key, level)| Ask {
+                    price: price(key),
+                    size: f64::from(level.size),
+                    timestamp: level.timestamp,
+                })
+                .collect(),
+            trades: book.trades.iter().cloned().collect(),
+        })
+    }
+
+    fn book(&mut self, symbol: &str) -> &mut SymbolBook {
+        self.books
+            .entry(String::from(symbol))
+            .or_insert_with(SymbolBook::default)
+    }
+}
+
+impl Default for BookBuilder {
+    fn default() -> BookBuilder {
+        BookBuilder::new()
+    }
+}
+
+/// Replays a DEEP capture through a `BookBuilder`, so books can be looked at as of successive
+/// points in time.
+pub struct BookReplay<R> {
+    messages: HistReader<R>,
+    builder: BookBuilder,
+    next: Option<Message>,
+}
+
+impl BookReplay<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<BookReplay<BufReader<File>>> {
+        BookReplay::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> BookReplay<R> {
+    pub fn new(inner: R) -> Result<BookReplay<R>> {
+        Ok(BookReplay {
+            messages: HistReader::new(inner)?,
+            builder: BookBuilder::new(),
+            next: None,
+        })
+    }
+
+    /// Applies every message sent at or before `timestamp`, in milliseconds since the Unix epoch.
+    ///
+    /// Replay only moves forward, so a timestamp earlier than a previous one leaves the books as
+    /// they are.
+    pub fn advance_to(&mut self, timestamp: f64) -> Result<()> {
+        loop {
+            let message = match self.next.take() {
+                Some(message) => message,
+                None => match self.messages.next() {
+                    Some(message) => message?,
+                    None => return Ok(()),
+                },
+            };
+            if message.timestamp().map_or(false, |t| t > timestamp) {
+                self.next = Some(message);
+                return Ok(());
+            }
+            self.builder.apply(&message);
+        }
+    }
+
+    /// The book for `symbol` as of `timestamp`, or `None` if it hasn't had any updates by then.
+    pub fn snapshot_at(&mut self, symbol: &str, timestamp: f64) -> Result<Option<BookSnapshot>> {
+        self.advance_to(timestamp)?;
+        Ok(self.builder.snapshot(symbol))
+    }
+
+    pub fn builder(&self) -> &BookBuilder {
+        &self.builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::{ByteOrder, LittleEndian};
+    use iextp::tests::{le_price, le_u32, message, segment, TIMESTAMP};
+    use iextp::DEEP_PROTOCOL_ID;
+    use pcap;
+
+    fn update(side: Side, complete: bool, size: u32, price: f64) -> Message {
+        Message::PriceLevelUpdate(PriceLevelUpdate {
+            symbol: String::from("ZIEXT"),
+            side: side,
+            is_event_complete: complete,
+            timestamp: TIMESTAMP,
+            size: size,
+            price: price,
+        })
+    }
+
+    fn trade(trade_id: f64, timestamp: f64) -> Trade {
+        Trade {
+            price: 99.05,
+            size: 100.0,
+            trade_id: trade_id,
+            is_iso: false,
+            is_odd_lot: false,
+            is_outside_regular_hours: false,
+            is_single_price_cross: false,
+            is_trade_through_exempt: false,
+            timestamp: timestamp,
+        }
+    }
+
+    fn prices(snapshot: &BookSnapshot) -> (Vec<f64>, Vec<f64>) {
+        (
+            snapshot.bids.iter().map(|bid| bid.price).collect(),
+            snapshot.asks.iter().map(|ask| ask.price).collect(),
+        )
+    }
+
+    #[test]
+    fn levels_are_sorted_best_first() {
+        let mut builder = BookBuilder::new();
+        builder.apply(&update(Side::Buy, true, 100, 99.05));
+        builder.apply(&update(Side::Buy, true, 200, 99.07));
+        builder.apply(&update(Side::Sell, true, 300, 99.11));
+        builder.apply(&update(Side::Sell, true, 400, 99.09));
+
+        let snapshot = builder.snapshot("ZIEXT").unwrap();
+        assert_eq!(prices(&snapshot), (vec![99.07, 99.05], vec![99.09, 99.11]));
+        assert_eq!(snapshot.bids[0].size, 200.0);
+        assert_eq!(snapshot.asks[0].size, 400.0);
+        assert_eq!(builder.snapshot("ZZZZ"), None);
+    }
+
+    #[test]
+    fn incomplete_events_are_held_back() {
+        let mut builder = BookBuilder::new();
+        builder.apply(&update(Side::Buy, true, 100, 99.05));
+
+        // A trade that takes out the bid and replaces it with a new one, sent as one event.
+        builder.apply(&update(Side::Buy, false, 0, 99.05));
+        builder.apply(&update(Side::Sell, false, 100, 99.06));
+        assert_eq!(
+            prices(&builder.snapshot("ZIEXT").unwrap()),
+            (vec![99.05], vec![])
+        );
+
+        builder.apply(&update(Side::Buy, true, 300, 99.04));
+        assert_eq!(
+            prices(&builder.snapshot("ZIEXT").unwrap()),
+            (vec![99.04], vec![99.06])
+        );
+    }
+
+    #[test]
+    fn trades_and_breaks() {
+        let mut builder = BookBuilder::with_max_trades(2);
+        for id in 1..4 {
+            builder.apply(&Message::TradeReport {
+                symbol: String::from("ZIEXT"),
+                trade: trade(f64::from(id), f64::from(id)),
+            });
+        }
+        let ids = |builder: &BookBuilder| -> Vec<f64> {
+            let snapshot = builder.snapshot("ZIEXT").unwrap();
+            snapshot.trades.iter().map(|trade| trade.trade_id).collect()
+        };
+        assert_eq!(ids(&builder), vec![3.0, 2.0]);
+
+        builder.apply(&Message::TradeBreak {
+            symbol: String::from("ZIEXT"),
+            trade: trade(3.0, 4.0),
+        });
+        assert_eq!(ids(&builder), vec![2.0]);
+        assert_eq!(builder.snapshot("ZIEXT").unwrap().timestamp, 4.0);
+    }
+
+    #[test]
+    fn replay() {
+        let level = |message_type: u8, flags: u8, size: u32, price: f64, offset: i64| {
+            let mut rest = le_u32(size);
+            rest.extend(le_price(price));
+            let mut data = message(message_type, flags, "ZIEXT", &rest);
+            let mut timestamp = vec![0; 8];
+            LittleEndian::write_i64(&mut timestamp, TIMESTAMP + offset);
+            data[2..10].copy_from_slice(&timestamp);
+            data
+        };
+        let first = segment(
+            DEEP_PROTOCOL_ID,
+            &[level(b'8', 1, 100, 99.05, 0), level(b'5', 1, 200, 99.07, 0)],
+        );
+        let second = segment(DEEP_PROTOCOL_ID, &[level(b'8', 1, 0, 99.05, 5_000_000)]);
+        let file = pcap::pcap_file(&[&first, &second]);
+
+        let start = iextp::millis(TIMESTAMP);
+        let mut replay = BookReplay::new(&file[..]).unwrap();
+        assert_eq!(replay.snapshot_at("ZIEXT", start - 1.0).unwrap(), None);
+
+        let snapshot = replay.snapshot_at("ZIEXT", start + 1.0).unwrap().unwrap();
+        assert_eq!(prices(&snapshot), (vec![99.05], vec![99.07]));
+
+        let snapshot = replay.snapshot_at("ZIEXT", start + 10.0).unwrap().unwrap();
+        assert_eq!(prices(&snapshot), (vec![], vec![99.07]));
+        assert_eq!(replay.builder().symbols(), vec!["ZIEXT"]);
+    }
+}
@@ -0,0 +1,596 @@
+//! Reading packets from pcap and pcapng capture files, the formats IEX publishes HIST data in.
+//!
+//! Only what's needed to get at the UDP payloads is decoded: Ethernet (with or without VLAN tags),
+//! Linux cooked and raw IP captures, carrying IPv4.
+
+use std::io::{self, Read};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use {IexError, Result};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const PCAPNG_SIMPLE_PACKET: u32 = 0x0000_0003;
+const PCAPNG_ENHANCED_PACKET: u32 = 0x0000_0006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_OPTION_END: u16 = 0;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+// Anything bigger than this is a corrupt length rather than a real packet.
+const MAX_BLOCK_LEN: usize = 16 * 1024 * 1024;
+
+pub const LINKTYPE_ETHERNET: u32 = 1;
+pub const LINKTYPE_RAW: u32 = 101;
+pub const LINKTYPE_LINUX_SLL: u32 = 113;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+const IPPROTO_UDP: u8 = 17;
+
+/// A single captured frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Packet {
+    /// When the packet was captured, in nanoseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The link-layer header type, such as `LINKTYPE_ETHERNET`.
+    pub link_type: u32,
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// The payload of the UDP datagram in this packet, or `None` if it isn't IPv4 UDP.
+    pub fn udp_payload(&self) -> Option<&[u8]> {
+        let ip = match self.link_type {
+            LINKTYPE_ETHERNET => ethernet_payload(&self.data)?,
+            LINKTYPE_LINUX_SLL => {
+                if self.data.len() < 16 || BigEndian::read_u16(&self.data[14..]) != ETHERTYPE_IPV4 {
+                    return None;
+                }
+                &self.data[16..]
+            }
+            LINKTYPE_RAW => &self.data[..],
+            _ => return None,
+        };
+        udp_payload(ip)
+    }
+}
+
+fn ethernet_payload(frame: &[u8]) -> Option<&[u8]> {
+    let mut offset = 12;
+    loop {
+        if frame.len() < offset + 2 {
+            return None;
+        }
+        match BigEndian::read_u16(&frame[offset..]) {
+            ETHERTYPE_VLAN | ETHERTYPE_QINQ => offset += 4,
+            ETHERTYPE_IPV4 => return Some(&frame[offset + 2..]),
+            _ => return None,
+        }
+    }
+}
+
+fn udp_payload(ip: &[u8]) -> Option<&[u8]> {
+    if ip.len() < 20 || ip[0] >> 4 != 4 || ip[9] != IPPROTO_UDP {
+        return None;
+    }
+    let header_len = usize::from(ip[0] & 0x0f) * 4;
+    let total_len = usize::from(BigEndian::read_u16(&ip[2..])).min(ip.len());
+    if total_len < header_len + 8 {
+        return None;
+    }
+    let udp = &ip[header_len..total_len];
+    let udp_len = usize::from(BigEndian::read_u16(&udp[4..])).min(udp.len());
+    if udp_len < 8 {
+        return None;
+    }
+    Some(&udp[8..udp_len])
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn u16(self, buf: &[u8]) -> u16 {
+        match self {
+            Endian::Little => LittleEndian::read_u16(buf),
+            Endian::Big => BigEndian::read_u16(buf),
+        }
+    }
+
+    fn u32(self, buf: &[u8]) -> u32 {
+        match self {
+            Endian::Little => LittleEndian::read_u32(buf),
+            Endian::Big => BigEndian::read_u32(buf),
+        }
+    }
+}
+
+struct Interface {
+    link_type: u32,
+    units_per_sec: u64,
+}
+
+enum Format {
+    Pcap {
+        endian: Endian,
+        units_per_sec: u64,
+        link_type: u32,
+    },
+    PcapNg {
+        endian: Endian,
+        interfaces: Vec<Interface>,
+    },
+}
+
+/// Reads packets from a pcap or pcapng stream, detecting the format from its first bytes.
+///
+/// The reader doesn't decompress anything, so wrap gzipped captures in a decoder first.
+pub struct PacketReader<R> {
+    inner: R,
+    format: Format,
+}
+
+impl<R: Read> PacketReader<R> {
+    pub fn new(mut inner: R) -> Result<PacketReader<R>> {
+        let mut magic = [0; 4];
+        inner.read_exact(&mut magic)?;
+        let format = match (LittleEndian::read_u32(&magic), BigEndian::read_u32(&magic)) {
+            (PCAPNG_SECTION_HEADER, _) => {
+                let mut header = [0; 8];
+                inner.read_exact(&mut header)?;
+                let endian = byte_order(&header[4..])?;
+                let len = block_len(endian.u32(&header), 12)?;
+                skip(&mut inner, len - 12)?;
+                Format::PcapNg {
+                    endian: endian,
+                    interfaces: Vec::new(),
+                }
+            }
+            (PCAP_MAGIC_MICROS, _) | (PCAP_MAGIC_NANOS, _) => {
+                pcap_format(&mut inner, &magic, Endian::Little)?
+            }
+            (_, PCAP_MAGIC_MICROS) | (_, PCAP_MAGIC_NANOS) => {
+                pcap_format(&mut inner, &magic, Endian::Big)?
+            }
+            _ => return Err(decode_error("not a pcap or pcapng file")),
+        };
+        Ok(PacketReader {
+            inner: inner,
+            format: format,
+        })
+    }
+
+    /// Reads the next packet, or returns `None` at the end of the capture.
+    pub fn next_packet(&mut self) -> Result<Option<Packet>> {
+        match self.format {
+            Format::Pcap { .. } => self.next_pcap_packet(),
+            Format::PcapNg { .. } => self.next_pcapng_packet(),
+        }
+    }
+
+    fn next_pcap_packet(&mut self) -> Result<Option<Packet>> {
+        let (endian, units_per_sec, link_type) = match self.format {
+            Format::Pcap {
+                endian,
+                units_per_sec,
+                link_type,
+            } => (endian, units_per_sec, link_type),
+            _ => unreachable!(),
+        };
+
+        let mut header = [0; 16];
+        if !read_or_eof(&mut self.inner, &mut header)? {
+            return Ok(None);
+        }
+        let secs = u64::from(endian.u32(&header));
+        let frac = u64::from(endian.u32(&header[4..]));
+        let len = endian.u32(&header[8..]) as usize;
+        if len > MAX_BLOCK_LEN {
+            return Err(decode_error(format!("packet length {} is too long", len)));
+        }
+        let mut data = vec![0; len];
+        self.inner.read_exact(&mut data)?;
+
+        Ok(Some(Packet {
+            timestamp: secs * 1_000_000_000 + nanos(frac, units_per_sec)?,
+            link_type: link_type,
+            data: data,
+        }))
+    }
+
+    fn next_pcapng_packet(&mut self) -> Result<Option<Packet>> {
+        loop {
+            let mut header = [0; 8];
+            if !read_or_eof(&mut self.inner, &mut header)? {
+                return Ok(None);
+            }
+
+            let (endian, interfaces) = match self.format {
+                Format::PcapNg {
+                    ref mut endian,
+                    ref mut interfaces,
+                } => (endian, interfaces),
+                _ => unreachable!(),
+            };
+
+            // A new section can switch byte order, so its length can only be read once the byte
+            // order magic that follows it has been.
+            if LittleEndian::read_u32(&header) == PCAPNG_SECTION_HEADER {
+                let mut magic = [0; 4];
+                self.inner.read_exact(&mut magic)?;
+                *endian = byte_order(&magic)?;
+                interfaces.clear();
+                let len = block_len(endian.u32(&header[4..]), 16)?;
+                skip(&mut self.inner, len - 12)?;
+                continue;
+            }
+
+            let block_type = endian.u32(&header);
+            let len = block_len(endian.u32(&header[4..]), 12)?;
+            let mut body = vec![0; len - 8];
+            self.inner.read_exact(&mut body)?;
+            body.truncate(len - 12);
+
+            match block_type {
+                PCAPNG_INTERFACE_DESCRIPTION => {
+                    if body.len() < 8 {
+                        return Err(decode_error("truncated interface description block"));
+                    }
+                    interfaces.push(Interface {
+                        link_type: u32::from(endian.u16(&body)),
+                        units_per_sec: units_per_sec(*endian, &body[8..]),
+                    });
+                }
+                PCAPNG_ENHANCED_PACKET => {
+                    if body.len() < 20 {
+                        return Err(decode_error("truncated enhanced packet block"));
+                    }
+                    let interface = interface(interfaces, endian.u32(&body) as usize)?;
+                    let ticks =
+                        u64::from(endian.u32(&body[4..])) << 32 | u64::from(endian.u32(&body[8..]));
+                    let captured = endian.u32(&body[12..]) as usize;
+                    if body.len() < 20 + captured {
+                        return Err(decode_error("truncated enhanced packet block"));
+                    }
+                    return Ok(Some(Packet {
+                        timestamp: nanos(ticks, interface.units_per_sec)?,
+                        link_type: interface.link_type,
+                        data: body[20..20 + captured].to_vec(),
+                    }));
+                }
+                PCAPNG_SIMPLE_PACKET => {
+                    if body.len() < 4 {
+                        return Err(decode_error("truncated simple packet block"));
+                    }
+                    let interface = interface(interfaces, 0)?;
+                    let original = endian.u32(&body) as usize;
+                    let captured = original.min(body.len() - 4);
+                    return Ok(Some(Packet {
+                        timestamp: 0,
+                        link_type: interface.link_type,
+                        data: body[4..4 + captured].to_vec(),
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for PacketReader<R> {
+    type Item = Result<Packet>;
+
+    fn next(&mut self) -> Option<Result<Packet>> {
+        match self.next_packet() {
+            Ok(Some(packet)) => Some(Ok(packet)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+fn pcap_format<R: Read>(inner: &mut R, magic: &[u8], endian: Endian) -> Result<Format> {
+    let mut header = [0; 20];
+    inner.read_exact(&mut header)?;
+    Ok(Format::Pcap {
+        endian: endian,
+        units_per_sec: if endian.u32(magic) == PCAP_MAGIC_NANOS {
+            1_000_000_000
+        } else {
+            1_000_000
+        },
+        link_type: endian.u32(&header[16..]),
+    })
+}
+
+fn byte_order(magic: &[u8]) -> Result<Endian> {
+    match LittleEndian::read_u32(magic) {
+        PCAPNG_BYTE_ORDER_MAGIC => Ok(Endian::Little),
+        _ if BigEndian::read_u32(magic) == PCAPNG_BYTE_ORDER_MAGIC => Ok(Endian::Big),
+        _ => Err(decode_error("bad pcapng byte order magic")),
+    }
+}
+
+fn block_len(len: u32, min: usize) -> Result<usize> {
+    let len = len as usize;
+    if len < min || len > MAX_BLOCK_LEN || len % 4 != 0 {
+        return Err(decode_error(format!("bad pcapng block length {}", len)));
+    }
+    Ok(len)
+}
+
+fn interface(interfaces: &[Interface], id: usize) -> Result<&Interface> {
+    interfaces
+        .get(id)
+        .ok_or_else(|| decode_error(format!("packet for undescribed interface {}", id)))
+}
+
+// The `if_tsresol` option gives the timestamp resolution as a power of ten, or of two if its high
+// bit is set. Without it, timestamps are in microseconds.
+fn units_per_sec(endian: Endian, mut options: &[u8]) -> u64 {
+    while options.len() >= 4 {
+        let code = endian.u16(options);
+        let len = usize::from(endian.u16(&options[2..]));
+        if code == PCAPNG_OPTION_END || options.len() < 4 + len {
+            break;
+        }
+        if code == PCAPNG_OPTION_TSRESOL && len >= 1 {
+            let resolution = options[4];
+            let exponent = u32::from(resolution & 0x7f).min(63);
+            return if resolution & 0x80 == 0 {
+                10u64.checked_pow(exponent).unwrap_or(1_000_000_000)
+            } else {
+                1u64 << exponent
+            };
+        }
+        options = &options[4 + (len + 3) / 4 * 4..];
+    }
+    1_000_000
+}
+
+// Converts a timestamp in `units_per_sec` units to nanoseconds. Whole-second resolutions and
+// corrupt timestamps can overflow a u64 of nanoseconds, and power-of-two resolutions don't divide
+// a second evenly, so the sum is done in u128.
+fn nanos(ticks: u64, units_per_sec: u64) -> Result<u64> {
+    let nanos = u128::from(ticks) * 1_000_000_000 / u128::from(units_per_sec);
+    if nanos > u128::from(u64::max_value()) {
+        return Err(decode_error(format!(
+            "timestamp {} at {} units a second is out of range",
+            ticks, units_per_sec
+        )));
+    }
+    Ok(nanos as u64)
+}
+
+fn skip<R: Read>(inner: &mut R, len: usize) -> Result<()> {
+    let skipped = io::copy(&mut inner.take(len as u64), &mut io::sink())?;
+    if skipped < len as u64 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated capture").into());
+    }
+    Ok(())
+}
+
+// Like `read_exact`, but returns false if the stream ends before any bytes are read.
+fn read_or_eof<R: Read>(inner: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match inner.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => {
+                return Err(
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated capture").into(),
+                )
+            }
+            Ok(n) => read += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn decode_error<S: Into<String>>(message: S) -> IexError {
+    IexError::Decode(message.into())
+}
+
+/// Wraps `payload` in Ethernet, IPv4 and UDP headers.
+#[cfg(test)]
+pub(crate) fn udp_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0; 14];
+    BigEndian::write_u16(&mut frame[12..], ETHERTYPE_IPV4);
+
+    let mut ip = vec![0; 20];
+    ip[0] = 0x45;
+    BigEndian::write_u16(&mut ip[2..], (28 + payload.len()) as u16);
+    ip[8] = 64;
+    ip[9] = IPPROTO_UDP;
+    frame.extend_from_slice(&ip);
+
+    let mut udp = vec![0; 8];
+    BigEndian::write_u16(&mut udp[0..], 10378);
+    BigEndian::write_u16(&mut udp[2..], 10378);
+    BigEndian::write_u16(&mut udp[4..], (8 + payload.len()) as u16);
+    frame.extend_from_slice(&udp);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Builds a little-endian, microsecond pcap file with one Ethernet frame per UDP payload.
+#[cfg(test)]
+pub(crate) fn pcap_file(payloads: &[&[u8]]) -> Vec<u8> {
+    let mut file = vec![0; 24];
+    LittleEndian::write_u32(&mut file[0..], PCAP_MAGIC_MICROS);
+    LittleEndian::write_u16(&mut file[4..], 2);
+    LittleEndian::write_u16(&mut file[6..], 4);
+    LittleEndian::write_u32(&mut file[16..], 65535);
+    LittleEndian::write_u32(&mut file[20..], LINKTYPE_ETHERNET);
+    for (i, payload) in payloads.iter().enumerate() {
+        let frame = udp_frame(payload);
+        let mut header = [0; 16];
+        LittleEndian::write_u32(&mut header[0..], 1_509_795_000 + i as u32);
+        LittleEndian::write_u32(&mut header[4..], 250);
+        LittleEndian::write_u32(&mut header[8..], frame.len() as u32);
+        LittleEndian::write_u32(&mut header[12..], frame.len() as u32);
+        file.extend_from_slice(&header);
+        file.extend_from_slice(&frame);
+    }
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block<B: ByteOrder>(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let padded = (body.len() + 3) / 4 * 4;
+        let mut block = vec![0; 8 + padded + 4];
+        B::write_u32(&mut block[0..], block_type);
+        B::write_u32(&mut block[4..], (12 + padded) as u32);
+        block[8..8 + body.len()].copy_from_slice(body);
+        let len = block.len();
+        B::write_u32(&mut block[len - 4..], (12 + padded) as u32);
+        block
+    }
+
+    fn pcapng_file<B: ByteOrder>(payloads: &[&[u8]]) -> Vec<u8> {
+        // An Ethernet interface with nanosecond timestamps.
+        pcapng_file_with::<B>(9, 1_509_795_000_123_456_789, payloads)
+    }
+
+    fn pcapng_file_with<B: ByteOrder>(tsresol: u8, ticks: u64, payloads: &[&[u8]]) -> Vec<u8> {
+        let mut section = vec![0; 16];
+        B::write_u32(&mut section[0..], PCAPNG_BYTE_ORDER_MAGIC);
+        B::write_u16(&mut section[4..], 1);
+        for byte in &mut section[8..16] {
+            *byte = 0xff;
+        }
+        let mut file = block::<B>(PCAPNG_SECTION_HEADER, &section);
+
+        let mut interface = vec![0; 16];
+        B::write_u16(&mut interface[0..], LINKTYPE_ETHERNET as u16);
+        B::write_u16(&mut interface[8..], PCAPNG_OPTION_TSRESOL);
+        B::write_u16(&mut interface[10..], 1);
+        interface[12] = tsresol;
+        file.extend(block::<B>(PCAPNG_INTERFACE_DESCRIPTION, &interface));
+
+        // Blocks the reader doesn't understand are skipped.
+        file.extend(block::<B>(0x0000_0004, &[1, 2, 3, 4]));
+
+        for payload in payloads {
+            let frame = udp_frame(payload);
+            let mut packet = vec![0; 20];
+            B::write_u32(&mut packet[4..], (ticks >> 32) as u32);
+            B::write_u32(&mut packet[8..], ticks as u32);
+            B::write_u32(&mut packet[12..], frame.len() as u32);
+            B::write_u32(&mut packet[16..], frame.len() as u32);
+            packet.extend_from_slice(&frame);
+            file.extend(block::<B>(PCAPNG_ENHANCED_PACKET, &packet));
+        }
+        file
+    }
+
+    fn payloads<R: Read>(reader: PacketReader<R>) -> Vec<Vec<u8>> {
+        reader
+            .map(|packet| packet.unwrap().udp_payload().unwrap().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn pcap() {
+        let file = pcap_file(&[b"hello", b"world!"]);
+        let mut reader = PacketReader::new(&file[..]).unwrap();
+        let packet = reader.next_packet().unwrap().unwrap();
+        assert_eq!(packet.timestamp, 1_509_795_000_000_250_000);
+        assert_eq!(packet.link_type, LINKTYPE_ETHERNET);
+        assert_eq!(packet.udp_payload(), Some(&b"hello"[..]));
+        assert_eq!(payloads(reader), vec![b"world!".to_vec()]);
+    }
+
+    #[test]
+    fn pcapng() {
+        let file = pcapng_file::<LittleEndian>(&[b"hello", b"world!"]);
+        let mut reader = PacketReader::new(&file[..]).unwrap();
+        let packet = reader.next_packet().unwrap().unwrap();
+        assert_eq!(packet.timestamp, 1_509_795_000_123_456_789);
+        assert_eq!(packet.udp_payload(), Some(&b"hello"[..]));
+        assert_eq!(payloads(reader), vec![b"world!".to_vec()]);
+    }
+
+    #[test]
+    fn pcapng_power_of_two_resolution() {
+        let ticks = (1_509_795_000 << 20) + (1 << 19);
+        let file = pcapng_file_with::<LittleEndian>(0x80 | 20, ticks, &[b"hello"]);
+        let mut reader = PacketReader::new(&file[..]).unwrap();
+        let packet = reader.next_packet().unwrap().unwrap();
+        assert_eq!(packet.timestamp, 1_509_795_000_500_000_000);
+    }
+
+    #[test]
+    fn pcapng_timestamp_out_of_range() {
+        // Whole-second timestamps this big are past what nanoseconds fit in.
+        let file = pcapng_file_with::<LittleEndian>(0, 1 << 40, &[b"hello"]);
+        let mut reader = PacketReader::new(&file[..]).unwrap();
+        match reader.next_packet() {
+            Err(IexError::Decode(_)) => {}
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn pcapng_big_endian() {
+        let file = pcapng_file::<BigEndian>(&[b"hello"]);
+        let reader = PacketReader::new(&file[..]).unwrap();
+        assert_eq!(payloads(reader), vec![b"hello".to_vec()]);
+    }
+
+    #[test]
+    fn not_a_capture() {
+        match PacketReader::new(&b"{\"symbol\": \"AAPL\"}"[..]) {
+            Err(IexError::Decode(_)) => {}
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn truncated_packet() {
+        let mut file = pcap_file(&[b"hello"]);
+        file.pop();
+        let mut reader = PacketReader::new(&file[..]).unwrap();
+        assert!(reader.next_packet().is_err());
+    }
+
+    #[test]
+    fn vlan_tagged_frame() {
+        let mut frame = udp_frame(b"hello");
+        let tag = [0x81, 0x00, 0x00, 0x2a];
+        for (i, byte) in tag.iter().enumerate() {
+            frame.insert(12 + i, *byte);
+        }
+        let packet = Packet {
+            timestamp: 0,
+            link_type: LINKTYPE_ETHERNET,
+            data: frame,
+        };
+        assert_eq!(packet.udp_payload(), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn not_udp() {
+        let mut frame = udp_frame(b"hello");
+        frame[14 + 9] = 6;
+        let packet = Packet {
+            timestamp: 0,
+            link_type: LINKTYPE_ETHERNET,
+            data: frame,
+        };
+        assert_eq!(packet.udp_payload(), None);
+    }
+}
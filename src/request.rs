@@ -0,0 +1,51 @@
+use std::marker::PhantomData;
+
+use serde;
+use serde_json;
+use serde_urlencoded;
+
+use transport::Response;
+use {IexError, Result};
+
+/// A request for a single API endpoint, along with the type its response deserializes to.
+///
+/// Both `IexClient` and `AsyncIexClient` send these, so building a request and parsing its
+/// response happen in one place regardless of which client is used.
+#[derive(Debug)]
+pub struct Request<R> {
+    pub path: String,
+    pub query: String,
+    response: PhantomData<R>,
+}
+
+impl<R> Request<R>
+where
+    R: serde::de::DeserializeOwned,
+{
+    pub fn new(path: String) -> Request<R> {
+        Request {
+            path: path,
+            query: String::new(),
+            response: PhantomData,
+        }
+    }
+
+    pub fn with_params<P>(path: String, params: P) -> Result<Request<R>>
+    where
+        P: serde::ser::Serialize,
+    {
+        Ok(Request {
+            path: path,
+            query: serde_urlencoded::to_string(params)?,
+            response: PhantomData,
+        })
+    }
+
+    /// Turns the raw response to this request into its typed result.
+    pub fn parse(&self, res: Response) -> Result<R> {
+        if !res.is_success() {
+            return Err(IexError::from_response(&self.path, res));
+        }
+        serde_json::from_str(&res.body).map_err(|e| IexError::deserialize(&self.path, &res.body, e))
+    }
+}
@@ -0,0 +1,618 @@
+//! Quote, news and trade streams over Server-Sent Events, as served by IEX's newer APIs.
+//!
+//! Streams are `futures::Stream`s that only read from the connection when they're polled, so a
+//! consumer that falls behind slows the server down through TCP flow control instead of having
+//! updates pile up in memory. Dropped connections are reopened with the `Last-Event-ID` of the
+//! last event seen, so the server can pick up where it left off.
+
+use std::collections::VecDeque;
+use std::mem;
+use std::time;
+
+use futures::{Async, Future, Poll, Stream};
+use reqwest;
+use reqwest::header::Headers;
+use reqwest::unstable::async as reqwest_async;
+use serde::de::DeserializeOwned;
+use serde_json;
+use serde_urlencoded;
+use tokio_core::reactor::{Handle, Timeout};
+
+use backoff::{Backoff, Reconnects};
+use transport::{self, Response};
+use types::{DeepMessage, DeepUpdate, News, Quote, Trade};
+use {IexError, Result};
+
+const DEFAULT_SSE_URL: &str = "https://cloud-sse.iexapis.com/stable";
+
+pub type IexStream<T> = Box<Stream<Item = T, Error = IexError>>;
+
+/// A single event from a `text/event-stream` body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    /// The last ID the server sent, on this event or an earlier one.
+    pub id: Option<String>,
+    /// The event's type, `message` unless the server said otherwise.
+    pub event: String,
+    pub data: String,
+}
+
+/// Splits a `text/event-stream` body into events, following the rules in the HTML standard.
+///
+/// The body can be pushed in chunks of any size. A carriage return at the very end of a chunk
+/// isn't treated as a line ending until the next chunk shows whether a line feed follows it.
+#[derive(Debug, Default)]
+pub struct EventParser {
+    buf: Vec<u8>,
+    started: bool,
+    event: String,
+    data: String,
+    last_event_id: Option<String>,
+    retry: Option<time::Duration>,
+}
+
+impl EventParser {
+    pub fn new() -> EventParser {
+        EventParser::default()
+    }
+
+    /// Adds the next part of the body, returning the events it completes.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.buf.extend_from_slice(bytes);
+        if !self.started {
+            if self.buf.len() < 3 && b"\xef\xbb\xbf".starts_with(&self.buf) {
+                return Vec::new();
+            }
+            if self.buf.starts_with(b"\xef\xbb\xbf") {
+                self.buf.drain(..3);
+            }
+            self.started = true;
+        }
+
+        let mut events = Vec::new();
+        let mut start = 0;
+        while let Some(pos) = self.buf[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+        {
+            let end = start + pos;
+            let next = match self.buf[end] {
+                b'\r' if end + 1 == self.buf.len() => break,
+                b'\r' if self.buf[end + 1] == b'\n' => end + 2,
+                _ => end + 1,
+            };
+            let line = String::from_utf8_lossy(&self.buf[start..end]).into_owned();
+            if let Some(event) = self.line(&line) {
+                events.push(event);
+            }
+            start = next;
+        }
+        self.buf.drain(..start);
+        events
+    }
+
+    /// The ID to send as `Last-Event-ID` when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_ref().map(|id| id.as_str())
+    }
+
+    /// How long the server has asked clients to wait before reconnecting.
+    pub fn retry(&self) -> Option<time::Duration> {
+        self.retry
+    }
+
+    fn line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.find(':') {
+            Some(colon) => {
+                let value = &line[colon + 1..];
+                let value = if value.starts_with(' ') {
+                    &value[1..]
+                } else {
+                    value
+                };
+                (&line[..colon], value)
+            }
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = String::from(value),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => {
+                self.last_event_id = if value.is_empty() {
+                    None
+                } else {
+                    Some(String::from(value))
+                }
+            }
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(time::Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = mem::replace(&mut self.event, String::new());
+        let mut data = mem::replace(&mut self.data, String::new());
+        if data.is_empty() {
+            return None;
+        }
+        data.pop();
+        Some(Event {
+            id: self.last_event_id.clone(),
+            event: if event.is_empty() {
+                String::from("message")
+            } else {
+                event
+            },
+            data: data,
+        })
+    }
+}
+
+/// Settings for an `SseClient`.
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+pub struct SseConfig {
+    /// The streaming API, including its version, e.g. `https://cloud-sse.iexapis.com/stable`.
+    #[builder(default = "String::from(DEFAULT_SSE_URL)")]
+    pub url: String,
+    /// The API token to send with every request, if the API needs one.
+    #[builder(default)]
+    pub token: Option<String>,
+    /// How long to wait between attempts to reconnect, and how many to make. A `retry` sent by
+    /// the server replaces `reconnect.delay`.
+    #[builder(default)]
+    pub reconnect: Backoff,
+}
+
+impl Default for SseConfig {
+    fn default() -> SseConfig {
+        SseConfigBuilder::default().build().unwrap()
+    }
+}
+
+/// A client for IEX's Server-Sent Events streams, running on a tokio reactor.
+pub struct SseClient {
+    http: reqwest_async::Client,
+    handle: Handle,
+    config: SseConfig,
+}
+
+impl SseClient {
+    pub fn new(handle: &Handle) -> Result<SseClient> {
+        SseClient::with_config(handle, SseConfig::default())
+    }
+
+    pub fn with_config(handle: &Handle, config: SseConfig) -> Result<SseClient> {
+        // Compressed streams can hold events back until a whole block has been decompressed.
+        let http = reqwest_async::Client::builder().gzip(false).build(handle)?;
+        Ok(SseClient {
+            http: http,
+            handle: handle.clone(),
+            config: config,
+        })
+    }
+
+    /// Quotes for `symbols`, from `/stocksUS`.
+    pub fn quotes(&self, symbols: &[&str]) -> Result<EventStream<Quote>> {
+        self.stream("/stocksUS", symbols, &[])
+    }
+
+    /// News about `symbols`, from `/news-stream`.
+    pub fn news(&self, symbols: &[&str]) -> Result<EventStream<News>> {
+        self.stream("/news-stream", symbols, &[])
+    }
+
+    /// Trades in `symbols` and the symbol each was in, from the trades channel of `/deep`.
+    pub fn trades(&self, symbols: &[&str]) -> Result<IexStream<(String, Trade)>> {
+        let messages: EventStream<DeepMessage> =
+            self.stream("/deep", symbols, &[("channels", "trades")])?;
+        Ok(Box::new(messages.filter_map(
+            |message| match message.update {
+                DeepUpdate::Trades(trade) => Some((message.symbol, trade)),
+                _ => None,
+            },
+        )))
+    }
+
+    /// Events from any stream at `path`, each decoded as a `T` or a JSON array of them.
+    pub fn stream<T>(
+        &self,
+        path: &str,
+        symbols: &[&str],
+        params: &[(&str, &str)],
+    ) -> Result<EventStream<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut query: Vec<(&str, Option<&str>)> = Vec::new();
+        let symbols = symbols.join(",");
+        if !symbols.is_empty() {
+            query.push(("symbols", Some(&symbols)));
+        }
+        query.extend(params.iter().map(|&(name, value)| (name, Some(value))));
+        query.push(("token", self.config.token.as_ref().map(|t| t.as_str())));
+        let query = serde_urlencoded::to_string(query)?;
+
+        let mut stream = EventStream {
+            http: self.http.clone(),
+            handle: self.handle.clone(),
+            path: String::from(path),
+            url: transport::url(&self.config.url, path, &query),
+            state: State::Done,
+            parser: EventParser::new(),
+            pending: VecDeque::new(),
+            reconnects: Reconnects::new(self.config.reconnect.clone()),
+        };
+        stream.state = stream.connect();
+        Ok(stream)
+    }
+}
+
+enum State {
+    Connecting(Box<Future<Item = reqwest_async::Response, Error = reqwest::Error>>),
+    Streaming(Box<reqwest_async::Decoder>),
+    // The server refused the stream, and its response is being read for the error.
+    Refused(u16, Box<Future<Item = Vec<u8>, Error = reqwest::Error>>),
+    Waiting(Timeout),
+    Done,
+}
+
+enum Step {
+    Next(State),
+    Events(Vec<Event>),
+    Reconnect,
+    Dropped(IexError),
+}
+
+/// A stream of `T`s decoded from the events on an SSE connection.
+///
+/// Connections that drop or fail are retried, and the stream only ends with an error once
+/// `reconnect.max_attempts` attempts in a row have failed or the server refuses the request
+/// outright. An event that can't be decoded is reported as an error, and the stream carries on
+/// after it.
+pub struct EventStream<T> {
+    http: reqwest_async::Client,
+    handle: Handle,
+    path: String,
+    url: String,
+    state: State,
+    parser: EventParser,
+    // Items from the chunk most recently read, waiting to be handed out.
+    pending: VecDeque<Result<T>>,
+    reconnects: Reconnects,
+}
+
+impl<T: DeserializeOwned> EventStream<T> {
+    /// The ID of the last event received, which is sent as `Last-Event-ID` on reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.parser.last_event_id()
+    }
+
+    fn connect(&self) -> State {
+        let mut headers = Headers::new();
+        headers.set_raw("Accept", "text/event-stream");
+        if let Some(id) = self.parser.last_event_id() {
+            headers.set_raw("Last-Event-ID", String::from(id));
+        }
+        State::Connecting(Box::new(self.http.get(&self.url).headers(headers).send()))
+    }
+
+    // Schedules another attempt after a dropped connection, or gives up with `err` if there have
+    // been too many.
+    fn reconnect(&mut self, err: IexError) -> Result<State> {
+        if let Some(retry) = self.parser.retry() {
+            self.reconnects.set_delay(retry);
+        }
+        match self.reconnects.next() {
+            Some(delay) => Ok(State::Waiting(Timeout::new(delay, &self.handle)?)),
+            None => Err(err),
+        }
+    }
+
+    fn decode(&mut self, event: Event) {
+        self.reconnects.reset();
+        let data = event.data.trim();
+        let items = if data.starts_with('[') {
+            serde_json::from_str::<Vec<T>>(data)
+        } else {
+            serde_json::from_str::<T>(data).map(|item| vec![item])
+        };
+        match items {
+            Ok(items) => self.pending.extend(items.into_iter().map(Ok)),
+            Err(err) => self
+                .pending
+                .push_back(Err(IexError::deserialize(&self.path, data, err))),
+        }
+    }
+
+    // Moves the connection along as far as it can go without blocking.
+    fn step(&mut self) -> Poll<(), IexError> {
+        let step = match self.state {
+            State::Connecting(ref mut res) => match res.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(res)) => {
+                    let status = res.status();
+                    let body = res.into_body();
+                    if status.is_success() {
+                        Step::Next(State::Streaming(Box::new(body)))
+                    } else {
+                        let body = body.fold(Vec::new(), |mut body, chunk| {
+                            body.extend_from_slice(&chunk);
+                            Ok::<_, reqwest::Error>(body)
+                        });
+                        Step::Next(State::Refused(status.as_u16(), Box::new(body)))
+                    }
+                }
+                Err(err) => Step::Dropped(IexError::from(err)),
+            },
+            State::Streaming(ref mut body) => match body.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(Some(chunk))) => Step::Events(self.parser.push(&chunk)),
+                Ok(Async::Ready(None)) => Step::Dropped(IexError::Stream(format!(
+                    "server closed the stream from {}",
+                    self.path
+                ))),
+                Err(err) => Step::Dropped(IexError::from(err)),
+            },
+            // Anything other than success means the server doesn't want this client, so there's
+            // no point reconnecting.
+            State::Refused(status, ref mut body) => {
+                let body = match body.poll() {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(body)) => String::from_utf8_lossy(&body).into_owned(),
+                    Err(_) => String::new(),
+                };
+                return Err(IexError::from_response(
+                    &self.path,
+                    Response::new(status, body),
+                ));
+            }
+            State::Waiting(ref mut timeout) => match timeout.poll()? {
+                Async::NotReady => return Ok(Async::NotReady),
+                Async::Ready(()) => Step::Reconnect,
+            },
+            State::Done => return Ok(Async::Ready(())),
+        };
+
+        match step {
+            Step::Next(state) => self.state = state,
+            Step::Events(events) => {
+                for event in events {
+                    self.decode(event);
+                }
+            }
+            Step::Reconnect => self.state = self.connect(),
+            Step::Dropped(err) => self.state = self.reconnect(err)?,
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T: DeserializeOwned> Stream for EventStream<T> {
+    type Item = T;
+    type Error = IexError;
+
+    fn poll(&mut self) -> Poll<Option<T>, IexError> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return item.map(|item| Async::Ready(Some(item)));
+            }
+            if let State::Done = self.state {
+                return Ok(Async::Ready(None));
+            }
+            match self.step() {
+                Ok(Async::Ready(())) => {}
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(err) => {
+                    self.state = State::Done;
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use tokio_core::reactor::Core;
+
+    const EVENT_STREAM: &str = "HTTP/1.1 200 OK\r\n\
+                                Content-Type: text/event-stream\r\n\
+                                Connection: close\r\n\r\n";
+
+    // Answers one connection with each of `responses` in turn, and returns the request heads.
+    fn serve(responses: Vec<String>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/stable", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let mut heads = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = Vec::new();
+                let mut byte = [0; 1];
+                while !head.ends_with(b"\r\n\r\n") {
+                    stream.read_exact(&mut byte).unwrap();
+                    head.push(byte[0]);
+                }
+                heads.push(String::from_utf8(head).unwrap().to_lowercase());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            heads
+        });
+        (url, server)
+    }
+
+    // An event whose data is `json`, which can run over several lines.
+    fn event(id: &str, json: &str) -> String {
+        let mut event = format!("id: {}\n", id);
+        for line in json.lines() {
+            event.push_str(&format!("data: {}\n", line));
+        }
+        event.push('\n');
+        event
+    }
+
+    fn client(core: &Core, url: &str, max_attempts: u32) -> SseClient {
+        let config = SseConfigBuilder::default()
+            .url(url)
+            .token(String::from("abc"))
+            .reconnect(Backoff {
+                delay: time::Duration::from_millis(10),
+                max_attempts: Some(max_attempts),
+                ..Backoff::default()
+            })
+            .build()
+            .unwrap();
+        SseClient::with_config(&core.handle(), config).unwrap()
+    }
+
+    #[test]
+    fn parse_events() {
+        let mut parser = EventParser::new();
+        let events = parser.push(
+            b": a comment\n\
+              event: quote\n\
+              data: {\"a\":\n\
+              data:1}\n\
+              id: 7\n\
+              retry: 2500\n\n\
+              data: second\r\n\r\n\
+              data",
+        );
+        assert_eq!(
+            events,
+            vec![
+                Event {
+                    id: Some(String::from("7")),
+                    event: String::from("quote"),
+                    data: String::from("{\"a\":\n1}"),
+                },
+                Event {
+                    id: Some(String::from("7")),
+                    event: String::from("message"),
+                    data: String::from("second"),
+                },
+            ]
+        );
+        assert_eq!(parser.retry(), Some(time::Duration::from_millis(2500)));
+        assert_eq!(parser.push(b"\n\n")[0].data, "");
+    }
+
+    #[test]
+    fn parse_in_pieces() {
+        let body = "\u{feff}data: a\r\rdata: b\r\n\r\nid\n\nid: 1\n:\n\n";
+        let mut parser = EventParser::new();
+        let events: Vec<Event> = body
+            .as_bytes()
+            .iter()
+            .flat_map(|byte| parser.push(&[*byte]))
+            .collect();
+        let data: Vec<&str> = events.iter().map(|event| event.data.as_str()).collect();
+        assert_eq!(data, vec!["a", "b"]);
+        assert_eq!(parser.last_event_id(), Some("1"));
+    }
+
+    #[test]
+    fn reconnect_with_last_event_id() {
+        let (url, server) = serve(vec![
+            format!(
+                "{}retry: 10\n{}",
+                EVENT_STREAM,
+                event("1", fixture!("quote"))
+            ),
+            format!("{}{}", EVENT_STREAM, event("2", fixture!("crypto"))),
+        ]);
+        let mut core = Core::new().unwrap();
+        let quotes = client(&core, &url, 1).quotes(&["aapl", "btcusdt"]).unwrap();
+        let quotes = core.run(quotes.take(3).collect()).unwrap();
+        let symbols: Vec<&str> = quotes.iter().map(|quote| quote.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["AAPL", "BTCUSDT", "ETHUSDT"]);
+
+        let heads = server.join().unwrap();
+        assert!(heads[0].starts_with("get /stable/stocksus?symbols=aapl%2cbtcusdt&token=abc "));
+        assert!(heads[0].contains("accept: text/event-stream"));
+        assert!(!heads[0].contains("last-event-id"));
+        assert!(heads[1].contains("last-event-id: 1\r\n"));
+    }
+
+    #[test]
+    fn news() {
+        let (url, server) = serve(vec![format!(
+            "{}{}",
+            EVENT_STREAM,
+            event("1", fixture!("news"))
+        )]);
+        let mut core = Core::new().unwrap();
+        let news = client(&core, &url, 0).news(&["aapl"]).unwrap();
+        let news = core.run(news.take(1).collect()).unwrap();
+        assert_eq!(news[0].datetime.to_rfc3339(), "2017-06-29T13:14:22-04:00");
+        assert_eq!(news[0].datetime.offset().local_minus_utc(), -4 * 3600);
+        assert_eq!(
+            news[0].related,
+            vec!["AAPL", "AMZN", "GOOG", "GOOGL", "MSFT"]
+        );
+        assert!(server.join().unwrap()[0].starts_with("get /stable/news-stream?symbols=aapl&"));
+    }
+
+    #[test]
+    fn trades() {
+        let (url, server) = serve(vec![format!(
+            "{}{}",
+            EVENT_STREAM,
+            event("1", fixture!("deep_stream"))
+        )]);
+        let mut core = Core::new().unwrap();
+        let trades = client(&core, &url, 0).trades(&["snap"]).unwrap();
+        let (symbol, trade) = core.run(trades.take(1).collect()).unwrap().remove(0);
+        assert_eq!(symbol, "SNAP");
+        assert_eq!(trade.trade_id, 2279736.0);
+        assert!(server.join().unwrap()[0].contains("channels=trades"));
+    }
+
+    #[test]
+    fn refused() {
+        let (url, server) = serve(vec![String::from(
+            "HTTP/1.1 401 Unauthorized\r\nContent-Length: 12\r\n\r\nUnauthorized",
+        )]);
+        let mut core = Core::new().unwrap();
+        let news = client(&core, &url, 3).news(&["aapl"]).unwrap();
+        match core.run(news.collect()) {
+            Err(IexError::Status { status, body, .. }) => {
+                assert_eq!(status, 401);
+                assert_eq!(body, "Unauthorized");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn gives_up_reconnecting() {
+        let (url, server) = serve(vec![String::from(EVENT_STREAM), String::from(EVENT_STREAM)]);
+        let mut core = Core::new().unwrap();
+        let news = client(&core, &url, 1).news(&["aapl"]).unwrap();
+        match core.run(news.collect()) {
+            Err(IexError::Stream(_)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,630 @@
+//! Real-time TOPS, Last and DEEP updates from IEX's socket.io streaming API.
+//!
+//! A `Subscriber` holds one connection to one channel on a background thread. Symbols are added
+//! and removed with `subscribe` and `unsubscribe`, and updates are read by iterating over the
+//! subscriber. If the connection drops, it's reopened and every symbol is subscribed to again.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{self, Instant};
+
+use reqwest::Url;
+use serde_json::{self, Map, Value};
+
+use backoff::{Backoff, Reconnects};
+use types::{DeepMessage, Last, Tops};
+use websocket::WebSocket;
+use {IexError, Result};
+
+const DEFAULT_STREAM_URL: &str = "wss://ws-api.iextrading.com";
+const ENGINE_IO_PATH: &str = "/socket.io/?EIO=3&transport=websocket";
+
+// How long the background thread waits on the socket before checking for new commands.
+const POLL_MILLIS: u64 = 50;
+
+/// The IEX streaming channels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    Tops,
+    Last,
+    Deep,
+}
+
+impl Channel {
+    fn namespace(&self) -> &'static str {
+        match self {
+            Channel::Tops => "/1.0/tops",
+            Channel::Last => "/1.0/last",
+            Channel::Deep => "/1.0/deep",
+        }
+    }
+
+    // The argument to the `subscribe` and `unsubscribe` events. DEEP takes a JSON document that
+    // also names its sub-channels, and the others take a comma-separated list.
+    fn subscription(&self, symbols: &[String]) -> String {
+        match self {
+            Channel::Tops | Channel::Last => symbols.join(","),
+            Channel::Deep => {
+                let mut doc = Map::new();
+                doc.insert(String::from("symbols"), Value::from(symbols.to_vec()));
+                doc.insert(String::from("channels"), Value::from(vec!["deep"]));
+                Value::Object(doc).to_string()
+            }
+        }
+    }
+
+    fn parse(&self, data: Value) -> serde_json::Result<StreamMessage> {
+        // IEX sends each message as a string of JSON rather than as an object.
+        let data = match data {
+            Value::String(s) => serde_json::from_str(&s)?,
+            data => data,
+        };
+        Ok(match self {
+            Channel::Tops => StreamMessage::Tops(serde_json::from_value(data)?),
+            Channel::Last => StreamMessage::Last(serde_json::from_value(data)?),
+            Channel::Deep => StreamMessage::Deep(serde_json::from_value(data)?),
+        })
+    }
+}
+
+/// An update from a streaming channel.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamMessage {
+    Tops(Tops),
+    Last(Last),
+    Deep(DeepMessage),
+}
+
+/// Settings for a `Subscriber`.
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(into))]
+pub struct StreamConfig {
+    /// The streaming server, without the `/socket.io` path.
+    #[builder(default = "String::from(DEFAULT_STREAM_URL)")]
+    pub url: String,
+    /// How long to wait for the connection and handshake.
+    #[builder(default = "time::Duration::from_secs(10)")]
+    pub timeout: time::Duration,
+    /// How long to wait between attempts to reconnect, and how many to make.
+    #[builder(default)]
+    pub reconnect: Backoff,
+}
+
+impl Default for StreamConfig {
+    fn default() -> StreamConfig {
+        StreamConfigBuilder::default().build().unwrap()
+    }
+}
+
+enum Command {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+/// A subscription to one of the IEX streaming channels.
+///
+/// Iterating blocks until the next update arrives. Errors are handed out as they happen but
+/// don't end the iteration: a dropped connection shows up as an `Err`, followed by updates again
+/// once it has been reopened. Iteration only ends once `reconnect.max_attempts` attempts in a row
+/// have failed.
+pub struct Subscriber {
+    commands: Option<Sender<Command>>,
+    messages: Receiver<Result<StreamMessage>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Subscriber {
+    /// Connects to `channel` on the IEX streaming API.
+    pub fn connect(channel: Channel) -> Result<Subscriber> {
+        Subscriber::with_config(channel, StreamConfig::default())
+    }
+
+    /// Connects to `channel` with the given settings. Fails if the first connection can't be
+    /// opened; connections dropped after that are reopened in the background.
+    pub fn with_config(channel: Channel, config: StreamConfig) -> Result<Subscriber> {
+        let session = Session::open(channel, &config)?;
+        let (commands, command_rx) = mpsc::channel();
+        let (message_tx, messages) = mpsc::channel();
+        let worker = Worker {
+            channel: channel,
+            reconnects: Reconnects::new(config.reconnect.clone()),
+            config: config,
+            commands: command_rx,
+            messages: message_tx,
+            symbols: Vec::new(),
+            session: Some(session),
+        };
+        Ok(Subscriber {
+            commands: Some(commands),
+            messages: messages,
+            worker: Some(thread::spawn(move || worker.run())),
+        })
+    }
+
+    /// Starts sending updates for `symbols`, on top of any already subscribed to.
+    pub fn subscribe(&self, symbols: &[&str]) -> Result<()> {
+        self.send(Command::Subscribe(to_strings(symbols)))
+    }
+
+    /// Stops sending updates for `symbols`.
+    pub fn unsubscribe(&self, symbols: &[&str]) -> Result<()> {
+        self.send(Command::Unsubscribe(to_strings(symbols)))
+    }
+
+    /// Waits up to `timeout` for the next update. Returns `None` if nothing arrived in time, or if
+    /// the subscriber has given up reconnecting.
+    pub fn next_timeout(&self, timeout: time::Duration) -> Option<Result<StreamMessage>> {
+        match self.messages.recv_timeout(timeout) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Closes the connection.
+    pub fn close(self) {}
+
+    fn send(&self, command: Command) -> Result<()> {
+        self.commands
+            .as_ref()
+            .and_then(|commands| commands.send(command).ok())
+            .ok_or_else(|| {
+                IexError::Stream(String::from("the subscriber has stopped reconnecting"))
+            })
+    }
+}
+
+impl Iterator for Subscriber {
+    type Item = Result<StreamMessage>;
+
+    fn next(&mut self) -> Option<Result<StreamMessage>> {
+        self.messages.recv().ok()
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        // Hanging up the command channel tells the worker to close the connection and stop.
+        self.commands.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn to_strings(symbols: &[&str]) -> Vec<String> {
+    symbols.iter().map(|symbol| String::from(*symbol)).collect()
+}
+
+// The Engine.IO open packet, which starts every session.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Handshake {
+    ping_interval: u64,
+    ping_timeout: u64,
+}
+
+/// One connection to the streaming server, joined to a channel's namespace.
+struct Session {
+    socket: WebSocket,
+    channel: Channel,
+    ping_interval: time::Duration,
+    ping_timeout: time::Duration,
+    last_ping: Instant,
+    last_heard: Instant,
+    // Whether the server has acknowledged joining the namespace, so it's safe to subscribe.
+    joined: bool,
+}
+
+impl Session {
+    fn open(channel: Channel, config: &StreamConfig) -> Result<Session> {
+        let url = format!("{}{}", config.url.trim_end_matches('/'), ENGINE_IO_PATH);
+        let url = Url::parse(&url)
+            .map_err(|err| IexError::Stream(format!("invalid streaming URL {}: {}", url, err)))?;
+        let mut socket = WebSocket::connect(&url, config.timeout)?;
+
+        let open = socket.read_text()?.ok_or(IexError::Timeout)?;
+        if !open.starts_with('0') {
+            return Err(IexError::Stream(format!(
+                "expected an Engine.IO open packet, got `{}`",
+                open
+            )));
+        }
+        let handshake: Handshake = serde_json::from_str(&open[1..]).map_err(|err| {
+            IexError::Stream(format!("invalid Engine.IO open packet `{}`: {}", open, err))
+        })?;
+
+        socket.set_read_timeout(time::Duration::from_millis(POLL_MILLIS))?;
+        socket.send_text(&format!("40{},", channel.namespace()))?;
+        Ok(Session {
+            socket: socket,
+            channel: channel,
+            ping_interval: time::Duration::from_millis(handshake.ping_interval),
+            ping_timeout: time::Duration::from_millis(handshake.ping_timeout),
+            last_ping: Instant::now(),
+            last_heard: Instant::now(),
+            joined: false,
+        })
+    }
+
+    /// Reads the next packet, if one arrives before the poll interval is up. The outer `Result`
+    /// is the state of the connection, and the inner one a message that may not have decoded.
+    fn poll(&mut self, symbols: &[String]) -> Result<Option<Result<StreamMessage>>> {
+        if self.last_heard.elapsed() > self.ping_interval + self.ping_timeout {
+            return Err(IexError::Stream(String::from(
+                "server stopped answering pings",
+            )));
+        }
+        if self.last_ping.elapsed() >= self.ping_interval {
+            self.socket.send_text("2")?;
+            self.last_ping = Instant::now();
+        }
+
+        let packet = match self.socket.read_text()? {
+            Some(packet) => packet,
+            None => return Ok(None),
+        };
+        self.last_heard = Instant::now();
+        match packet.chars().next() {
+            Some('1') => Err(IexError::Stream(String::from(
+                "server closed the Engine.IO session",
+            ))),
+            Some('2') => {
+                self.socket.send_text("3")?;
+                Ok(None)
+            }
+            Some('4') => self.socket_io(&packet[1..], symbols),
+            _ => Ok(None),
+        }
+    }
+
+    // Handles a socket.io packet: a type digit, then the namespace if it isn't the default one,
+    // then a comma, an optional acknowledgement id and a JSON payload.
+    fn socket_io(
+        &mut self,
+        packet: &str,
+        symbols: &[String],
+    ) -> Result<Option<Result<StreamMessage>>> {
+        if packet.is_empty() {
+            return Ok(None);
+        }
+        let (kind, rest) = packet.split_at(1);
+        let (namespace, payload) = if rest.starts_with('/') {
+            match rest.find(',') {
+                Some(comma) => (&rest[..comma], &rest[comma + 1..]),
+                None => (rest, ""),
+            }
+        } else {
+            ("/", rest)
+        };
+        if namespace != self.channel.namespace() {
+            return Ok(None);
+        }
+
+        match kind {
+            "0" => {
+                self.joined = true;
+                self.emit("subscribe", symbols)?;
+                Ok(None)
+            }
+            "1" => Err(IexError::Stream(format!(
+                "server disconnected from {}",
+                namespace
+            ))),
+            "2" => Ok(self.event(payload.trim_start_matches(|c: char| c.is_ascii_digit()))),
+            "4" => Err(IexError::Stream(format!(
+                "server refused {}: {}",
+                namespace, payload
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn event(&self, payload: &str) -> Option<Result<StreamMessage>> {
+        let decode_error = |err: serde_json::Error| {
+            IexError::Stream(format!(
+                "couldn't decode {} message `{}`: {}",
+                self.channel.namespace(),
+                payload,
+                err
+            ))
+        };
+        let mut args = match serde_json::from_str::<Vec<Value>>(payload) {
+            Ok(args) => args.into_iter(),
+            Err(err) => return Some(Err(decode_error(err))),
+        };
+        match (args.next(), args.next()) {
+            (Some(Value::String(ref name)), Some(data)) if name == "message" => {
+                Some(self.channel.parse(data).map_err(decode_error))
+            }
+            _ => None,
+        }
+    }
+
+    fn emit(&mut self, event: &str, symbols: &[String]) -> Result<()> {
+        if !self.joined || symbols.is_empty() {
+            return Ok(());
+        }
+        let args = Value::from(vec![
+            Value::from(event),
+            Value::from(self.channel.subscription(symbols)),
+        ]);
+        self.socket
+            .send_text(&format!("42{},{}", self.channel.namespace(), args))
+    }
+}
+
+struct Worker {
+    channel: Channel,
+    config: StreamConfig,
+    reconnects: Reconnects,
+    commands: Receiver<Command>,
+    messages: Sender<Result<StreamMessage>>,
+    symbols: Vec<String>,
+    session: Option<Session>,
+}
+
+impl Worker {
+    fn run(mut self) {
+        while self.handle_commands() {
+            if self.session.is_none() {
+                let delay = match self.reconnects.next() {
+                    Some(delay) => delay,
+                    None => break,
+                };
+                if !self.wait(delay) {
+                    break;
+                }
+                match Session::open(self.channel, &self.config) {
+                    Ok(session) => self.session = Some(session),
+                    Err(err) => {
+                        if self.messages.send(Err(err)).is_err() {
+                            break;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let polled = match self.session {
+                Some(ref mut session) => session.poll(&self.symbols),
+                None => continue,
+            };
+            let delivered = match polled {
+                Ok(Some(message)) => {
+                    self.reconnects.reset();
+                    self.messages.send(message).is_ok()
+                }
+                Ok(None) => true,
+                Err(err) => self.disconnect(err),
+            };
+            if !delivered {
+                break;
+            }
+        }
+
+        if let Some(mut session) = self.session.take() {
+            session.socket.close();
+        }
+    }
+
+    // Applies any commands from the subscriber. Returns false once the subscriber has gone away.
+    fn handle_commands(&mut self) -> bool {
+        loop {
+            let emitted = match self.commands.try_recv() {
+                Ok(Command::Subscribe(symbols)) => {
+                    let added: Vec<String> = symbols
+                        .into_iter()
+                        .filter(|symbol| !self.symbols.contains(symbol))
+                        .collect();
+                    self.symbols.extend(added.iter().cloned());
+                    self.emit("subscribe", &added)
+                }
+                Ok(Command::Unsubscribe(symbols)) => {
+                    let removed: Vec<String> = symbols
+                        .into_iter()
+                        .filter(|symbol| self.symbols.contains(symbol))
+                        .collect();
+                    self.symbols.retain(|symbol| !removed.contains(symbol));
+                    self.emit("unsubscribe", &removed)
+                }
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            };
+            if let Err(err) = emitted {
+                if !self.disconnect(err) {
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn emit(&mut self, event: &str, symbols: &[String]) -> Result<()> {
+        match self.session {
+            Some(ref mut session) => session.emit(event, symbols),
+            None => Ok(()),
+        }
+    }
+
+    // Drops the session so it's reopened, and tells the subscriber why. Returns false if the
+    // subscriber has gone away.
+    fn disconnect(&mut self, err: IexError) -> bool {
+        self.session = None;
+        self.messages.send(Err(err)).is_ok()
+    }
+
+    // Sleeps for `delay`, still taking commands. Returns false if the subscriber goes away.
+    fn wait(&mut self, delay: time::Duration) -> bool {
+        let deadline = Instant::now() + delay;
+        loop {
+            if !self.handle_commands() {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            thread::sleep((deadline - now).min(time::Duration::from_millis(POLL_MILLIS)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use websocket::tests::TestServer;
+
+    const OPEN: &str = r#"0{"sid":"abc","upgrades":[],"pingInterval":25000,"pingTimeout":60000}"#;
+
+    const SNAP: &str = r#"{\"symbol\":\"SNAP\",\"marketPercent\":0.00901,\"bidSize\":200,\"bidPrice\":110.94,\"askSize\":100,\"askPrice\":111.82,\"volume\":177265,\"lastSalePrice\":111.76,\"lastSaleSize\":5,\"lastSaleTime\":1480446905681,\"lastUpdated\":1480446910557,\"sector\":\"softwareservices\",\"securityType\":\"commonstock\"}"#;
+
+    fn config(listener: &TcpListener) -> StreamConfig {
+        StreamConfigBuilder::default()
+            .url(format!("ws://{}", listener.local_addr().unwrap()))
+            .timeout(time::Duration::from_secs(5))
+            .reconnect(Backoff {
+                delay: time::Duration::from_millis(10),
+                max_attempts: Some(2),
+                ..Backoff::default()
+            })
+            .build()
+            .unwrap()
+    }
+
+    // Plays the server's part of opening a session and joining `namespace`.
+    fn join(listener: &TcpListener, namespace: &str) -> TestServer {
+        let (mut server, target) = TestServer::accept(listener);
+        assert_eq!(target, "/socket.io/?EIO=3&transport=websocket");
+        server.send_text(OPEN);
+        server.send_text("40");
+        assert_eq!(server.read_text(), format!("40{},", namespace));
+        server.send_text(&format!("40{},", namespace));
+        server
+    }
+
+    fn tops(message: Option<Result<StreamMessage>>) -> Tops {
+        match message {
+            Some(Ok(StreamMessage::Tops(tops))) => tops,
+            message => panic!("unexpected message: {:?}", message),
+        }
+    }
+
+    #[test]
+    fn subscribe_and_receive() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = config(&listener);
+        let server = thread::spawn(move || {
+            let mut server = join(&listener, "/1.0/tops");
+            assert_eq!(server.read_text(), r#"42/1.0/tops,["subscribe","snap,fb"]"#);
+            server.send_text(&format!(r#"42/1.0/tops,["message","{}"]"#, SNAP));
+            server.send_text(r#"42/1.0/tops,["message","not json"]"#);
+            server.send_text("2");
+            assert_eq!(server.read_text(), "3");
+            server.send_text(&format!(r#"42/1.0/tops,["message","{}"]"#, SNAP));
+            assert_eq!(server.read_text(), r#"42/1.0/tops,["unsubscribe","fb"]"#);
+        });
+
+        let mut subscriber = Subscriber::with_config(Channel::Tops, config).unwrap();
+        subscriber.subscribe(&["snap", "fb"]).unwrap();
+        assert_eq!(tops(subscriber.next()).bid_price, 110.94);
+        match subscriber.next() {
+            Some(Err(IexError::Stream(_))) => {}
+            message => panic!("unexpected message: {:?}", message),
+        }
+        assert_eq!(tops(subscriber.next()).symbol, "SNAP");
+        subscriber.unsubscribe(&["fb", "msft"]).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn reconnect_and_resubscribe() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = config(&listener);
+        let server = thread::spawn(move || {
+            {
+                let mut server = join(&listener, "/1.0/last");
+                assert_eq!(server.read_text(), r#"42/1.0/last,["subscribe","snap"]"#);
+            }
+            let mut server = join(&listener, "/1.0/last");
+            assert_eq!(server.read_text(), r#"42/1.0/last,["subscribe","snap"]"#);
+            server.send_text(
+                r#"42/1.0/last,["message","{\"symbol\":\"SNAP\",\"price\":111.76,\"size\":5,\"time\":1480446905681,\"seq\":1}"]"#,
+            );
+            // Leave the listener open until the client has gone.
+            server.read_frame();
+        });
+
+        let mut subscriber = Subscriber::with_config(Channel::Last, config).unwrap();
+        subscriber.subscribe(&["snap"]).unwrap();
+        match subscriber.next() {
+            Some(Err(IexError::Stream(_))) => {}
+            message => panic!("unexpected message: {:?}", message),
+        }
+        match subscriber.next() {
+            Some(Ok(StreamMessage::Last(last))) => assert_eq!(last.price, 111.76),
+            message => panic!("unexpected message: {:?}", message),
+        }
+        subscriber.close();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn gives_up_reconnecting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = config(&listener);
+        let server = thread::spawn(move || {
+            join(&listener, "/1.0/deep");
+        });
+
+        let mut subscriber = Subscriber::with_config(Channel::Deep, config).unwrap();
+        server.join().unwrap();
+        // The dropped connection, then one error per failed attempt to reconnect.
+        for _ in 0..3 {
+            assert!(subscriber.next().unwrap().is_err());
+        }
+        assert!(subscriber.next().is_none());
+        assert!(subscriber.subscribe(&["snap"]).is_err());
+    }
+
+    #[test]
+    fn gives_up_on_sessions_that_keep_dropping() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = config(&listener);
+        let server = thread::spawn(move || {
+            // Every session joins and then hangs up before sending anything.
+            for _ in 0..3 {
+                join(&listener, "/1.0/tops");
+            }
+            listener
+        });
+
+        let mut subscriber = Subscriber::with_config(Channel::Tops, config).unwrap();
+        for _ in 0..3 {
+            assert!(subscriber.next().unwrap().is_err());
+        }
+        assert!(subscriber.next().is_none());
+
+        let listener = server.join().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_err());
+    }
+
+    // Talks to the real IEX streaming API, so it's only run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn live_tops() {
+        let mut subscriber = Subscriber::connect(Channel::Tops).unwrap();
+        subscriber.subscribe(&["snap", "fb"]).unwrap();
+        assert!(subscriber.next().unwrap().is_ok());
+    }
+
+    #[test]
+    fn deep_subscription() {
+        let symbols = vec![String::from("snap"), String::from("fb")];
+        let subscription: Value =
+            serde_json::from_str(&Channel::Deep.subscription(&symbols)).unwrap();
+        assert_eq!(subscription["symbols"], Value::from(vec!["snap", "fb"]));
+        assert_eq!(subscription["channels"], Value::from(vec!["deep"]));
+        assert_eq!(Channel::Tops.subscription(&symbols), "snap,fb");
+    }
+}
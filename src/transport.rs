@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use reqwest;
+
+use {IexError, Result};
+
+/// A raw response from the IEX API, before it has been deserialized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn new<S: Into<String>>(status: u16, body: S) -> Response {
+        Response {
+            status: status,
+            body: body.into(),
+        }
+    }
+
+    pub fn ok<S: Into<String>>(body: S) -> Response {
+        Response::new(200, body)
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+}
+
+/// Performs the request/response step for an `IexClient`.
+///
+/// `path` is the endpoint path relative to the API version, e.g. `/stock/aapl/quote`, and `query`
+/// is the URL-encoded query string without the leading `?`, or empty if there are no parameters.
+pub trait Transport: Send + Sync {
+    fn get(&self, path: &str, query: &str) -> Result<Response>;
+}
+
+/// The default transport, which talks to the IEX API over HTTP using reqwest.
+pub struct HttpTransport {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpTransport {
+    /// `base_url` should include the API version, e.g. `https://api.iextrading.com/1.0`.
+    pub fn new<S: Into<String>>(http: reqwest::Client, base_url: S) -> HttpTransport {
+        HttpTransport {
+            http: http,
+            base_url: base_url.into(),
+        }
+    }
+
+    pub fn url(&self, path: &str, query: &str) -> String {
+        url(&self.base_url, path, query)
+    }
+}
+
+pub(crate) fn url(base_url: &str, path: &str, query: &str) -> String {
+    let mut url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(query);
+    }
+    url
+}
+
+impl Transport for HttpTransport {
+    fn get(&self, path: &str, query: &str) -> Result<Response> {
+        let mut res = self.http.get(&self.url(path, query)).send()?;
+        Ok(Response::new(res.status().as_u16(), res.text()?))
+    }
+}
+
+/// A transport that serves canned responses from memory, keyed by path and query string.
+///
+/// Requests that don't match any canned response fail with a transport error, so a test that asks
+/// for the wrong path or query can't be mistaken for one that hit an error on purpose.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTransport {
+    responses: HashMap<(String, String), Response>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> MemoryTransport {
+        MemoryTransport::default()
+    }
+
+    /// Serves `body` with a 200 status for requests matching `path` and `query`.
+    pub fn with_json<S: Into<String>>(self, path: &str, query: &str, body: S) -> MemoryTransport {
+        self.with_response(path, query, Response::ok(body))
+    }
+
+    pub fn with_response(mut self, path: &str, query: &str, response: Response) -> MemoryTransport {
+        self.insert(path, query, response);
+        self
+    }
+
+    pub fn insert(&mut self, path: &str, query: &str, response: Response) {
+        self.responses
+            .insert((String::from(path), String::from(query)), response);
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn get(&self, path: &str, query: &str) -> Result<Response> {
+        self.responses
+            .get(&(String::from(path), String::from(query)))
+            .cloned()
+            .ok_or_else(|| {
+                IexError::Transport(format_err!("no canned response for {}?{}", path, query))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_url() {
+        let http = HttpTransport::new(reqwest::Client::new(), "http://localhost:8080/beta/");
+        assert_eq!(
+            http.url("/stock/aapl/quote", ""),
+            "http://localhost:8080/beta/stock/aapl/quote"
+        );
+        assert_eq!(
+            http.url("/stock/aapl/chart/1m", "chartSimplify=true"),
+            "http://localhost:8080/beta/stock/aapl/chart/1m?chartSimplify=true"
+        );
+    }
+
+    #[test]
+    fn memory_matches_path_and_query() {
+        let memory = MemoryTransport::new()
+            .with_json("/stock/aapl/price", "", "190.5")
+            .with_json("/stock/aapl/chart/1m", "chartSimplify=true", "[]");
+
+        assert_eq!(
+            memory.get("/stock/aapl/price", "").unwrap(),
+            Response::ok("190.5")
+        );
+        assert_eq!(
+            memory
+                .get("/stock/aapl/chart/1m", "chartSimplify=true")
+                .unwrap(),
+            Response::ok("[]")
+        );
+        match memory.get("/stock/aapl/chart/1m", "") {
+            Err(IexError::Transport(_)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
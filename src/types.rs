@@ -1,10 +1,20 @@
+//! Response types for the IEX API. These are re-exported from the crate root.
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A stock quote, from `/stock/{symbol}/quote` and the market lists, or a crypto quote from
+/// `/stock/market/crypto`.
+///
+/// Crypto quotes have no IEX, delayed or fundamental data, so those fields are `None`, and are the
+/// only quotes with `bid_price`, `bid_size`, `ask_price` and `ask_size`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Quote {
     pub symbol: String,
     pub company_name: String,
     pub primary_exchange: String,
-    pub sector: String,
+    pub sector: Option<String>,
     pub calculation_price: String,
     pub open: f64,
     pub open_time: f64,
@@ -17,46 +27,53 @@ pub struct Quote {
     pub latest_time: String,
     pub latest_update: f64,
     pub latest_volume: f64,
-    pub iex_realtime_price: f64,
-    pub iex_realtime_size: f64,
-    pub iex_last_updated: f64,
-    pub delayed_price: f64,
-    pub delayed_price_time: f64,
+    pub iex_realtime_price: Option<f64>,
+    pub iex_realtime_size: Option<f64>,
+    pub iex_last_updated: Option<f64>,
+    pub delayed_price: Option<f64>,
+    pub delayed_price_time: Option<f64>,
     pub previous_close: f64,
     pub change: f64,
     pub change_percent: f64,
-    pub iex_market_percent: f64,
-    pub iex_volume: f64,
-    pub avg_total_volume: f64,
-    pub iex_bid_price: f64,
-    pub iex_bid_size: f64,
-    pub iex_ask_price: f64,
-    pub iex_ask_size: f64,
-    pub market_cap: f64,
+    pub iex_market_percent: Option<f64>,
+    pub iex_volume: Option<f64>,
+    pub avg_total_volume: Option<f64>,
+    pub iex_bid_price: Option<f64>,
+    pub iex_bid_size: Option<f64>,
+    pub iex_ask_price: Option<f64>,
+    pub iex_ask_size: Option<f64>,
+    pub market_cap: Option<f64>,
     pub pe_ratio: Option<f64>,
-    pub week52_high: f64,
-    pub week52_low: f64,
-    pub ytd_change: f64,
+    pub week52_high: Option<f64>,
+    pub week52_low: Option<f64>,
+    pub ytd_change: Option<f64>,
+    pub bid_price: Option<f64>,
+    pub bid_size: Option<f64>,
+    pub ask_price: Option<f64>,
+    pub ask_size: Option<f64>,
 }
 
+/// A bid on the IEX order book.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Bid {
     pub price: f64,
     pub size: f64,
     pub timestamp: f64,
 }
 
+/// An ask on the IEX order book.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Ask {
     pub price: f64,
     pub size: f64,
     pub timestamp: f64,
 }
 
+/// An execution on IEX.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Trade {
     pub price: f64,
     pub size: f64,
@@ -70,15 +87,17 @@ pub struct Trade {
     pub timestamp: f64,
 }
 
+/// An IEX system event, such as the start or end of market hours.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SystemEvent {
     pub system_event: String,
     pub timestamp: f64,
 }
 
+/// A quote plus the IEX order book and recent trades, from `/stock/{symbol}/book`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Book {
     pub quote: Quote,
     pub bids: Vec<Bid>,
@@ -87,8 +106,9 @@ pub struct Book {
     pub system_event: SystemEvent,
 }
 
+/// Company information, from `/stock/{symbol}/company`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Company {
     pub symbol: String,
     pub company_name: String,
@@ -102,8 +122,9 @@ pub struct Company {
     pub sector: String,
 }
 
+/// A 15 minute delayed quote, from `/stock/{symbol}/delayed-quote`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DelayedQuote {
     pub symbol: String,
     pub delayed_price: f64,
@@ -115,8 +136,9 @@ pub struct DelayedQuote {
     pub processed_time: f64,
 }
 
+/// A dividend, from `/stock/{symbol}/dividends/{range}`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Dividend {
     pub ex_date: String,
     pub payment_date: String,
@@ -130,8 +152,9 @@ pub struct Dividend {
     pub indicated: String,
 }
 
+/// Earnings for a single fiscal period.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Earning {
     #[serde(rename = "actualEPS")]
     pub actual_eps: f64,
@@ -149,15 +172,17 @@ pub struct Earning {
     pub fiscal_end_date: String,
 }
 
+/// The last four quarters of earnings, from `/stock/{symbol}/earnings`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Earnings {
     pub symbol: String,
     pub earnings: Vec<Earning>,
 }
 
+/// Effective spread on a single venue, from `/stock/{symbol}/effective-spread`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EffectiveSpread {
     pub volume: f64,
     pub venue: String,
@@ -167,8 +192,9 @@ pub struct EffectiveSpread {
     pub price_improvement: f64,
 }
 
+/// Financial data for a single reporting period.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Financial {
     pub report_date: String,
     pub gross_profit: f64,
@@ -191,15 +217,17 @@ pub struct Financial {
     pub cash_flow: f64,
 }
 
+/// The last four quarters of financial data, from `/stock/{symbol}/financials`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Financials {
     pub symbol: String,
     pub financials: Vec<Financial>,
 }
 
+/// A threshold security, from `/stock/market/threshold-securities`.
 #[serde(rename_all = "PascalCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct IEXRegulationSHOThresholdSecurity {
     pub trade_date: String,
     #[serde(rename = "SymbolinINETSymbology")]
@@ -211,8 +239,9 @@ pub struct IEXRegulationSHOThresholdSecurity {
     pub security_name: String,
 }
 
+/// Short interest in an IEX-listed security, from `/stock/{symbol}/short-interest`.
 #[serde(rename_all = "PascalCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct IEXShortInterest {
     pub settlement_date: String,
     #[serde(rename = "SymbolinINETSymbology")]
@@ -233,8 +262,9 @@ pub struct IEXShortInterest {
     pub revision_flag: String,
 }
 
+/// Key stats, from `/stock/{symbol}/stats`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Stats {
     pub company_name: String,
     pub marketcap: f64,
@@ -295,32 +325,61 @@ pub struct Stats {
     pub day30_change_percent: f64,
 }
 
+/// The URL of a company logo, from `/stock/{symbol}/logo`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Logo {
     pub url: String,
 }
 
+/// A news item, from `/stock/{symbol}/news` or `/stock/market/news`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct News {
-    pub datetime: String,
+    pub datetime: DateTime<FixedOffset>,
     pub headline: String,
     pub source: String,
     pub url: String,
     pub summary: String,
-    pub related: String,
+    /// The symbols the item relates to.
+    #[serde(with = "comma_separated")]
+    pub related: Vec<String>,
+}
+
+// The API sends lists of symbols as a single comma-separated string.
+mod comma_separated {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(items: &[String], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&items.join(","))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.split(',')
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect())
+    }
 }
 
+/// A price and the time it was recorded.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Price {
     pub price: f64,
     pub time: f64,
 }
 
+/// The official open and close and the day's range, from `/stock/{symbol}/ohlc`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct OHLC {
     pub open: Price,
     pub close: Price,
@@ -328,8 +387,9 @@ pub struct OHLC {
     pub low: Option<f64>,
 }
 
+/// The previous trading day, from `/stock/{symbol}/previous`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Previous {
     pub symbol: String,
     pub date: String,
@@ -344,15 +404,17 @@ pub struct Previous {
     pub vwap: f64,
 }
 
+/// Symbols relevant to a stock, from `/stock/{symbol}/relevant`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Relevant {
     pub peers: bool,
     pub symbols: Vec<String>,
 }
 
+/// A stock split, from `/stock/{symbol}/splits/{range}`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Split {
     pub ex_date: String,
     pub declared_date: String,
@@ -363,8 +425,9 @@ pub struct Split {
     pub for_factor: f64,
 }
 
+/// A single daily bar, from `/stock/{symbol}/chart/{range}`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ChartDataPoint {
     pub date: String,
     pub open: f64,
@@ -380,8 +443,73 @@ pub struct ChartDataPoint {
     pub change_over_time: f64,
 }
 
+/// The bars returned by `/stock/{symbol}/chart/{range}`. Their shape depends on the range.
+///
+/// An empty chart always deserializes as `Daily`.
+#[serde(untagged)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Chart {
+    /// Daily bars, returned for every range other than `1d` and `dynamic`.
+    Daily(Vec<ChartDataPoint>),
+    /// Minute bars, returned for the `1d` range.
+    Intraday(Vec<MinuteBar>),
+    /// Returned for the `dynamic` range, which is `1d` if it's a trading day and `1m` otherwise.
+    Dynamic { range: String, data: Box<Chart> },
+}
+
+impl Chart {
+    /// The daily bars, if this is a daily chart or a dynamic chart that resolved to one.
+    pub fn daily(&self) -> Option<&Vec<ChartDataPoint>> {
+        match self {
+            Chart::Daily(bars) => Some(bars),
+            Chart::Intraday(_) => None,
+            Chart::Dynamic { data, .. } => data.daily(),
+        }
+    }
+
+    /// The minute bars, if this is an intraday chart or a dynamic chart that resolved to one.
+    pub fn intraday(&self) -> Option<&Vec<MinuteBar>> {
+        match self {
+            Chart::Daily(_) => None,
+            Chart::Intraday(bars) => Some(bars),
+            Chart::Dynamic { data, .. } => data.intraday(),
+        }
+    }
+}
+
+/// A single minute bar, from `/stock/{symbol}/chart/date/{date}` or the `1d` chart.
+///
+/// The unprefixed fields only cover trading on IEX, so they're missing (or -1) for minutes with no
+/// IEX trades. The `market_` fields cover all US markets.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MinuteBar {
+    pub date: String,
+    pub minute: String,
+    pub label: String,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub average: Option<f64>,
+    pub volume: f64,
+    pub notional: f64,
+    pub number_of_trades: f64,
+    pub market_high: f64,
+    pub market_low: f64,
+    pub market_average: f64,
+    pub market_volume: f64,
+    pub market_notional: f64,
+    pub market_number_of_trades: f64,
+    pub open: Option<f64>,
+    pub close: Option<f64>,
+    pub market_open: Option<f64>,
+    pub market_close: Option<f64>,
+    pub change_over_time: Option<f64>,
+    pub market_change_over_time: Option<f64>,
+}
+
+/// Volume traded on a single venue, from `/stock/{symbol}/volume-by-venue`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct VolumeByVenue {
     pub volume: f64,
     pub venue: String,
@@ -390,14 +518,1255 @@ pub struct VolumeByVenue {
     pub avg_market_percent: f64,
 }
 
+/// Volume traded on a single venue across all US markets, from `/market`.
 #[serde(rename_all = "camelCase")]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarketVolume {
+    pub mic: String,
+    pub tape_id: String,
+    pub venue_name: String,
+    pub volume: f64,
+    pub tape_a: f64,
+    pub tape_b: f64,
+    pub tape_c: f64,
+    pub market_percent: f64,
+    pub last_updated: f64,
+}
+
+/// The performance of a single sector, from `/stock/market/sector-performance`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SectorPerformance {
+    #[serde(rename = "type")]
+    pub performance_type: String,
+    pub name: String,
+    pub performance: f64,
+    pub last_updated: f64,
+}
+
+/// A symbol from one of the reference lists, such as `/ref-data/symbols`.
+///
+/// Each list fills in a different subset of the fields: `/ref-data/iex/symbols` only has the
+/// symbol, date and whether it's enabled, and only the OTC, mutual fund and international lists
+/// have an exchange, region and currency.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Symbol {
     pub symbol: String,
-    pub name: String,
+    pub name: Option<String>,
     pub date: String,
     pub is_enabled: bool,
     #[serde(rename = "type")]
-    pub symbol_type: String,
-    pub iex_id: String,
+    pub symbol_type: Option<SymbolType>,
+    pub iex_id: Option<String>,
+    pub exchange: Option<String>,
+    pub region: Option<String>,
+    pub currency: Option<String>,
+}
+
+/// The kind of security a `Symbol` is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolType {
+    CommonStock,
+    PreferredStock,
+    ETF,
+    ADR,
+    REIT,
+    ClosedEndFund,
+    OpenEndFund,
+    SecondaryIssue,
+    LimitedPartnership,
+    Warrant,
+    Right,
+    Unit,
+    StructuredProduct,
+    Temporary,
+    NotApplicable,
+    /// A type code this crate doesn't know about.
+    Other(String),
+}
+
+impl SymbolType {
+    /// The code the API uses for this type, e.g. `cs` for common stock.
+    pub fn code(&self) -> &str {
+        match self {
+            SymbolType::CommonStock => "cs",
+            SymbolType::PreferredStock => "ps",
+            SymbolType::ETF => "et",
+            SymbolType::ADR => "ad",
+            SymbolType::REIT => "re",
+            SymbolType::ClosedEndFund => "cef",
+            SymbolType::OpenEndFund => "oef",
+            SymbolType::SecondaryIssue => "si",
+            SymbolType::LimitedPartnership => "lp",
+            SymbolType::Warrant => "wt",
+            SymbolType::Right => "rt",
+            SymbolType::Unit => "ut",
+            SymbolType::StructuredProduct => "struct",
+            SymbolType::Temporary => "temp",
+            SymbolType::NotApplicable => "N/A",
+            SymbolType::Other(code) => code,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for SymbolType {
+    fn from(code: &'a str) -> SymbolType {
+        match code {
+            "cs" => SymbolType::CommonStock,
+            "ps" => SymbolType::PreferredStock,
+            "et" => SymbolType::ETF,
+            "ad" => SymbolType::ADR,
+            "re" => SymbolType::REIT,
+            "cef" => SymbolType::ClosedEndFund,
+            "oef" => SymbolType::OpenEndFund,
+            "si" => SymbolType::SecondaryIssue,
+            "lp" => SymbolType::LimitedPartnership,
+            "wt" => SymbolType::Warrant,
+            "rt" => SymbolType::Right,
+            "ut" => SymbolType::Unit,
+            "struct" => SymbolType::StructuredProduct,
+            "temp" => SymbolType::Temporary,
+            "N/A" => SymbolType::NotApplicable,
+            code => SymbolType::Other(String::from(code)),
+        }
+    }
+}
+
+impl Serialize for SymbolType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for SymbolType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SymbolType, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Ok(SymbolType::from(code.as_str()))
+    }
+}
+
+/// A corporate action taking effect on a trading day, from `/ref-data/daily-list/corporate-actions`.
+///
+/// The daily-list endpoints send every value as a string, with an empty string for missing values.
+#[serde(rename_all = "PascalCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CorporateAction {
+    #[serde(rename = "RecordID")]
+    pub record_id: String,
+    pub daily_list_timestamp: String,
+    pub effective_date: String,
+    pub issue_event: String,
+    #[serde(rename = "CurrentSymbolinINETSymbology")]
+    pub current_symbolin_inet_symbology: String,
+    #[serde(rename = "CurrentSymbolinCQSSymbology")]
+    pub current_symbolin_cqs_symbology: String,
+    #[serde(rename = "CurrentSymbolinCMSSymbology")]
+    pub current_symbolin_cms_symbology: String,
+    #[serde(rename = "NewSymbolinINETSymbology")]
+    pub new_symbolin_inet_symbology: String,
+    #[serde(rename = "NewSymbolinCQSSymbology")]
+    pub new_symbolin_cqs_symbology: String,
+    #[serde(rename = "NewSymbolinCMSSymbology")]
+    pub new_symbolin_cms_symbology: String,
+    pub current_security_name: String,
+    pub new_security_name: String,
+    pub current_company_name: String,
+    pub new_company_name: String,
+    pub current_listing_center: String,
+    pub new_listing_center: String,
+    pub delisting_reason: String,
+    pub current_round_lot_size: String,
+    pub new_round_lot_size: String,
+    #[serde(rename = "CurrentLULDTierIndicator")]
+    pub current_luld_tier_indicator: String,
+    #[serde(rename = "NewLULDTierIndicator")]
+    pub new_luld_tier_indicator: String,
+    pub expiration_date: String,
+    pub separation_date: String,
+    pub settlement_date: String,
+    pub maturity_date: String,
+    pub redemption_date: String,
+    pub current_financial_status: String,
+    pub new_financial_status: String,
+    pub when_issued_flag: String,
+    pub when_distributed_flag: String,
+    #[serde(rename = "IPOFlag")]
+    pub ipo_flag: String,
+    #[serde(rename = "NotesforEachEntry")]
+    pub notes_for_each_entry: String,
+    pub record_update_time: String,
+}
+
+/// A dividend or stock split announcement, from `/ref-data/daily-list/dividends`.
+#[serde(rename_all = "PascalCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DailyListDividend {
+    #[serde(rename = "RecordID")]
+    pub record_id: String,
+    pub daily_list_timestamp: String,
+    pub event_type: String,
+    #[serde(rename = "SymbolinINETSymbology")]
+    pub symbolin_inet_symbology: String,
+    #[serde(rename = "SymbolinCQSSymbology")]
+    pub symbolin_cqs_symbology: String,
+    #[serde(rename = "SymbolinCMSSymbology")]
+    pub symbolin_cms_symbology: String,
+    pub security_name: String,
+    pub company_name: String,
+    pub declaration_date: String,
+    pub amount_description: String,
+    pub payment_frequency: String,
+    pub ex_date: String,
+    pub record_date: String,
+    pub payment_date: String,
+    #[serde(rename = "DividendTypeID")]
+    pub dividend_type_id: String,
+    pub stock_adjustment_factor: String,
+    pub stock_amount: String,
+    pub cash_amount: String,
+    pub post_split_shares: String,
+    pub pre_split_shares: String,
+    pub qualified_dividend: String,
+    pub exercise_price_amount: String,
+    #[serde(rename = "ElectionorExpirationDate")]
+    pub election_or_expiration_date: String,
+    pub gross_amount: String,
+    pub net_amount: String,
+    pub basis_notes: String,
+    #[serde(rename = "NotesforEachEntry")]
+    pub notes_for_each_entry: String,
+    pub record_update_time: String,
+}
+
+/// A dividend or split going ex on the next trading day, from `/ref-data/daily-list/next-day-ex-date`.
+#[serde(rename_all = "PascalCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NextDayExDate {
+    #[serde(rename = "RecordID")]
+    pub record_id: String,
+    pub daily_list_timestamp: String,
+    pub ex_date: String,
+    #[serde(rename = "SymbolinINETSymbology")]
+    pub symbolin_inet_symbology: String,
+    #[serde(rename = "SymbolinCQSSymbology")]
+    pub symbolin_cqs_symbology: String,
+    #[serde(rename = "SymbolinCMSSymbology")]
+    pub symbolin_cms_symbology: String,
+    pub security_name: String,
+    pub company_name: String,
+    #[serde(rename = "DividendTypeID")]
+    pub dividend_type_id: String,
+    pub amount_description: String,
+    pub payment_frequency: String,
+    pub stock_adjustment_factor: String,
+    pub stock_amount: String,
+    pub cash_amount: String,
+    pub post_split_shares: String,
+    pub pre_split_shares: String,
+    pub qualified_dividend: String,
+    pub exercise_price_amount: String,
+    #[serde(rename = "ElectionorExpirationDate")]
+    pub election_or_expiration_date: String,
+    pub gross_amount: String,
+    pub net_amount: String,
+    pub basis_notes: String,
+    #[serde(rename = "NotesforEachEntry")]
+    pub notes_for_each_entry: String,
+    pub record_update_time: String,
+}
+
+/// A security in IEX's symbol directory, from `/ref-data/daily-list/symbol-directory`.
+#[serde(rename_all = "PascalCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SymbolDirectoryEntry {
+    #[serde(rename = "RecordID")]
+    pub record_id: String,
+    pub daily_list_timestamp: String,
+    #[serde(rename = "SymbolinINETSymbology")]
+    pub symbolin_inet_symbology: String,
+    #[serde(rename = "SymbolinCQSSymbology")]
+    pub symbolin_cqs_symbology: String,
+    #[serde(rename = "SymbolinCMSSymbology")]
+    pub symbolin_cms_symbology: String,
+    pub security_name: String,
+    pub company_name: String,
+    pub test_issue: String,
+    pub issue_description: String,
+    pub issue_type: String,
+    pub issue_sub_type: String,
+    #[serde(rename = "SIC")]
+    pub sic: String,
+    pub transfer_agent: String,
+    pub financial_status: String,
+    pub round_lot_size: String,
+    pub previous_official_price: String,
+    pub previous_closing_price: String,
+    pub adjusted_previous_official_price: String,
+    pub adjusted_previous_closing_price: String,
+    pub record_update_time: String,
+}
+
+/// A HIST capture that can be downloaded, from `/hist`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistFile {
+    /// Where to download the capture, a gzipped pcap file.
+    pub link: String,
+    pub date: String,
+    /// `TOPS` or `DEEP`.
+    pub feed: String,
+    pub version: String,
+    pub protocol: String,
+    /// The size of the download in bytes. IEX sends this as a string.
+    #[serde(deserialize_with = "u64_or_string")]
+    pub size: u64,
+}
+
+impl HistFile {
+    /// A file name for the capture in IEX's own naming scheme, e.g.
+    /// `20180127_IEXTP1_DEEP1.0.pcap.gz`.
+    pub fn file_name(&self) -> String {
+        format!(
+            "{}_{}_{}{}.pcap.gz",
+            self.date, self.protocol, self.feed, self.version
+        )
+    }
+}
+
+fn u64_or_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Int(u64),
+        Str(String),
+    }
+
+    match ::serde::Deserialize::deserialize(deserializer)? {
+        Size::Int(i) => Ok(i),
+        Size::Str(s) => s.trim().parse().map_err(::serde::de::Error::custom),
+    }
+}
+
+/// IEX's best bid and offer and last sale for a symbol, from `/tops`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tops {
+    pub symbol: String,
+    pub market_percent: f64,
+    pub bid_size: f64,
+    pub bid_price: f64,
+    pub ask_size: f64,
+    pub ask_price: f64,
+    pub volume: f64,
+    pub last_sale_price: f64,
+    pub last_sale_size: f64,
+    pub last_sale_time: f64,
+    pub last_updated: f64,
+    pub sector: String,
+    pub security_type: String,
+}
+
+/// The last sale on IEX for a symbol, from `/tops/last`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Last {
+    pub symbol: String,
+    pub price: f64,
+    pub size: f64,
+    pub time: f64,
+}
+
+/// IEX's full depth of book, trades and status for a symbol, from `/deep`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Deep {
+    pub symbol: String,
+    pub market_percent: f64,
+    pub volume: f64,
+    pub last_sale_price: f64,
+    pub last_sale_size: f64,
+    pub last_sale_time: f64,
+    pub last_updated: f64,
+    pub bids: Vec<Bid>,
+    pub asks: Vec<Ask>,
+    pub system_event: SystemEvent,
+    pub trading_status: TradingStatus,
+    pub op_halt_status: OpHaltStatus,
+    pub ssr_status: SSRStatus,
+    pub security_event: SecurityEvent,
+    pub trades: Vec<Trade>,
+    pub trade_breaks: Vec<Trade>,
+}
+
+/// The bids and asks on IEX for a symbol, from `/deep/book`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeepBook {
+    pub bids: Vec<Bid>,
+    pub asks: Vec<Ask>,
+}
+
+/// Whether a symbol is trading, halted or paused, from `/deep/trading-status`.
+///
+/// `status` is `H` (halted), `O` (halt released into an order acceptance period), `P` (paused)
+/// or `T` (trading). `reason` is only meaningful when the symbol isn't trading.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TradingStatus {
+    pub status: String,
+    pub reason: String,
+    pub timestamp: f64,
+}
+
+/// Whether IEX has halted trading in a symbol for operational reasons, from
+/// `/deep/op-halt-status`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OpHaltStatus {
+    pub is_halted: bool,
+    pub timestamp: f64,
+}
+
+/// Whether a symbol is under the Reg SHO short sale price test restriction, from
+/// `/deep/ssr-status`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SSRStatus {
+    #[serde(rename = "isSSR")]
+    pub is_ssr: bool,
+    pub detail: String,
+    pub timestamp: f64,
+}
+
+/// The open or close of trading in a symbol, from `/deep/security-event`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SecurityEvent {
+    pub security_event: String,
+    pub timestamp: f64,
+}
+
+/// The state of an IEX auction, from `/deep/auction`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Auction {
+    pub auction_type: String,
+    pub paired_shares: f64,
+    pub imbalance_shares: f64,
+    pub reference_price: f64,
+    pub indicative_price: f64,
+    pub auction_book_price: f64,
+    pub collar_reference_price: f64,
+    pub lower_collar_price: f64,
+    pub upper_collar_price: f64,
+    pub extension_number: f64,
+    pub start_time: String,
+    pub last_update: f64,
+}
+
+/// The official opening or closing price of an IEX-listed symbol, from `/deep/official-price`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OfficialPrice {
+    pub price_type: String,
+    pub price: f64,
+    pub timestamp: f64,
+}
+
+/// A single update on the DEEP streaming channel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeepMessage {
+    pub symbol: String,
+    #[serde(flatten)]
+    pub update: DeepUpdate,
+}
+
+/// What a `DeepMessage` updates, named by its `messageType`.
+#[serde(tag = "messageType", content = "data", rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum DeepUpdate {
+    Book(DeepBook),
+    Trades(Trade),
+    TradeBreaks(Trade),
+    SystemEvent(SystemEvent),
+    TradingStatus(TradingStatus),
+    OpHaltStatus(OpHaltStatus),
+    SsrStatus(SSRStatus),
+    SecurityEvent(SecurityEvent),
+    Auction(Auction),
+    OfficialPrice(OfficialPrice),
+}
+
+/// A single intraday statistic and when it was last updated.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stat {
+    pub value: f64,
+    pub last_updated: f64,
+}
+
+/// IEX's trading so far today, from `/stats/intraday`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IntradayStats {
+    pub volume: Stat,
+    pub symbols_traded: Stat,
+    pub routed_volume: Stat,
+    pub notional: Stat,
+    pub market_share: Stat,
+}
+
+/// IEX's trading on a single day, from `/stats/recent` and `/stats/historical/daily`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DailyStats {
+    pub date: String,
+    pub volume: f64,
+    pub routed_volume: f64,
+    pub market_share: f64,
+    #[serde(deserialize_with = "bool_or_int")]
+    pub is_halfday: bool,
+    pub lit_volume: f64,
+}
+
+// `/stats/historical/daily` sends flags as 0 or 1, where `/stats/recent` sends booleans.
+fn bool_or_int<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Int(u64),
+    }
+
+    match ::serde::Deserialize::deserialize(deserializer)? {
+        Flag::Bool(b) => Ok(b),
+        Flag::Int(i) => Ok(i != 0),
+    }
+}
+
+/// IEX's record for a single statistic.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub record_value: f64,
+    pub record_date: String,
+    pub previous_day_value: f64,
+    pub avg30_value: f64,
+}
+
+/// IEX's records, from `/stats/records`.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordStats {
+    pub volume: Record,
+    pub symbols_traded: Record,
+    pub routed_volume: Record,
+    pub notional: Record,
+}
+
+/// IEX's trading over a single month, from `/stats/historical`.
+///
+/// The `binNPercent` fields are the share of volume from orders of at most N shares, and the
+/// `binNTrades` fields count trades of at least N shares.
+#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoricalStats {
+    pub date: String,
+    pub average_daily_volume: f64,
+    pub average_daily_routed_volume: f64,
+    pub average_market_share: f64,
+    pub average_order_size: f64,
+    pub average_fill_size: f64,
+    pub bin100_percent: f64,
+    pub bin101_percent: f64,
+    pub bin200_percent: f64,
+    pub bin300_percent: f64,
+    pub bin400_percent: f64,
+    pub bin500_percent: f64,
+    pub bin1000_percent: f64,
+    pub bin5000_percent: f64,
+    pub bin10000_percent: f64,
+    pub bin10000_trades: f64,
+    pub bin20000_trades: f64,
+    pub bin50000_trades: f64,
+    pub unique_symbols_traded: f64,
+    pub block_percent: f64,
+    pub self_cross_percent: f64,
+    pub etf_percent: f64,
+    pub large_cap_percent: f64,
+    pub mid_cap_percent: f64,
+    pub small_cap_percent: f64,
+}
+
+/// The data returned for a single symbol by the batch endpoints. Only the requested types are
+/// present.
+#[serde(rename_all = "kebab-case")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub book: Option<Book>,
+    pub chart: Option<Chart>,
+    pub company: Option<Company>,
+    pub delayed_quote: Option<DelayedQuote>,
+    pub dividends: Option<Vec<Dividend>>,
+    pub earnings: Option<Earnings>,
+    pub effective_spread: Option<Vec<EffectiveSpread>>,
+    pub financials: Option<Financials>,
+    pub logo: Option<Logo>,
+    pub news: Option<Vec<News>>,
+    pub ohlc: Option<OHLC>,
+    pub peers: Option<Vec<String>>,
+    pub previous: Option<Previous>,
+    pub price: Option<f64>,
+    pub quote: Option<Quote>,
+    pub relevant: Option<Relevant>,
+    pub splits: Option<Vec<Split>>,
+    pub stats: Option<Stats>,
+    pub volume_by_venue: Option<Vec<VolumeByVenue>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde;
+    use serde_json;
+    use std::collections::HashMap;
+
+    fn parse<T>(json: &str) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn quote() {
+        let quote: Quote = parse(fixture!("quote"));
+        assert_eq!(quote.symbol, "AAPL");
+        assert_eq!(quote.company_name, "Apple Inc.");
+        assert_eq!(quote.primary_exchange, "Nasdaq Global Select");
+        assert_eq!(quote.calculation_price, "tops");
+        assert_eq!(quote.open_time, 1506605400394.0);
+        assert_eq!(quote.high, Some(154.8));
+        assert_eq!(quote.low, Some(153.25));
+        assert_eq!(quote.latest_price, 158.73);
+        assert_eq!(quote.latest_time, "September 19, 2017");
+        assert_eq!(quote.iex_realtime_price, Some(158.71));
+        assert_eq!(quote.iex_bid_price, Some(153.01));
+        assert_eq!(quote.iex_ask_size, Some(100.0));
+        assert_eq!(quote.market_cap, Some(751627174400.0));
+        assert_eq!(quote.pe_ratio, Some(16.86));
+        assert_eq!(quote.week52_high, Some(159.65));
+        assert_eq!(quote.week52_low, Some(93.63));
+        assert_eq!(quote.ytd_change, Some(0.3665));
+    }
+
+    #[test]
+    fn quote_with_nulls() {
+        let quote: Quote = parse(fixture!("quote_closed"));
+        assert_eq!(quote.symbol, "ZIEXT");
+        assert_eq!(quote.calculation_price, "close");
+        assert_eq!(quote.high, None);
+        assert_eq!(quote.low, None);
+        assert_eq!(quote.pe_ratio, None);
+    }
+
+    #[test]
+    fn crypto() {
+        let quotes: Vec<Quote> = parse(fixture!("crypto"));
+        assert_eq!(quotes[0].symbol, "BTCUSDT");
+        assert_eq!(quotes[0].sector, Some(String::from("cryptocurrency")));
+        assert_eq!(quotes[0].latest_price, 6444.7);
+        assert_eq!(quotes[0].market_cap, None);
+        assert_eq!(quotes[0].week52_high, None);
+        assert_eq!(quotes[0].bid_size, Some(0.19));
+        assert_eq!(quotes[1].sector, None);
+        assert_eq!(quotes[1].ask_price, Some(287.62));
+    }
+
+    #[test]
+    fn book() {
+        let book: Book = parse(fixture!("book"));
+        assert_eq!(book.quote.symbol, "AAPL");
+
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!(book.bids[0].price, 158.7);
+        assert_eq!(book.bids[0].size, 100.0);
+        assert_eq!(book.bids[0].timestamp, 1505851198059.0);
+
+        assert_eq!(book.asks.len(), 1);
+        assert_eq!(book.asks[0].price, 158.75);
+        assert_eq!(book.asks[0].size, 300.0);
+
+        assert_eq!(book.trades.len(), 1);
+        let trade = &book.trades[0];
+        assert_eq!(trade.price, 158.71);
+        assert_eq!(trade.trade_id, 517341294.0);
+        assert!(!trade.is_iso);
+        assert!(!trade.is_odd_lot);
+        assert!(!trade.is_outside_regular_hours);
+        assert!(!trade.is_single_price_cross);
+        assert!(!trade.is_trade_through_exempt);
+
+        assert_eq!(book.system_event.system_event, "C");
+        assert_eq!(book.system_event.timestamp, 1505854800001.0);
+    }
+
+    #[test]
+    fn clone_and_compare() {
+        let book: Book = parse(fixture!("book"));
+        let mut other = book.clone();
+        assert_eq!(book, other);
+        other.bids[0].size = 200.0;
+        assert_ne!(book, other);
+    }
+
+    #[test]
+    fn trade_renamed_fields() {
+        let trade: Trade = parse(
+            r#"{
+                "price": 156.1,
+                "size": 100,
+                "tradeId": 517341294,
+                "isISO": true,
+                "isOddLot": true,
+                "isOutsideRegularHours": true,
+                "isSinglePriceCross": true,
+                "isTradeThroughExempt": true,
+                "timestamp": 1494619192003
+            }"#,
+        );
+        assert!(trade.is_iso);
+        assert!(trade.is_odd_lot);
+        assert!(trade.is_outside_regular_hours);
+        assert!(trade.is_single_price_cross);
+        assert!(trade.is_trade_through_exempt);
+    }
+
+    #[test]
+    fn chart() {
+        let chart: Vec<ChartDataPoint> = parse(fixture!("chart"));
+        assert_eq!(chart.len(), 2);
+        assert_eq!(chart[0].date, "2017-09-19");
+        assert_eq!(chart[0].open, 159.51);
+        assert_eq!(chart[0].volume, 20567140.0);
+        assert_eq!(chart[0].label, "Sep 19");
+        assert_eq!(chart[0].change_over_time, 0.0);
+        assert_eq!(chart[1].unadjusted_volume, 52951364.0);
+        assert_eq!(chart[1].change_percent, -1.676);
+        assert_eq!(chart[1].vwap, 155.6612);
+    }
+
+    #[test]
+    fn chart_shapes() {
+        let daily: Chart = parse(fixture!("chart"));
+        assert_eq!(daily.daily().unwrap().len(), 2);
+        assert_eq!(daily.intraday(), None);
+
+        let intraday: Chart = parse(fixture!("chart_1d"));
+        assert_eq!(intraday.daily(), None);
+        assert_eq!(intraday.intraday().unwrap()[0].minute, "09:30");
+
+        match parse(fixture!("chart_dynamic")) {
+            Chart::Dynamic { range, data } => {
+                assert_eq!(range, "1d");
+                assert_eq!(data.intraday().unwrap().len(), 2);
+            }
+            chart => panic!("unexpected chart: {:?}", chart),
+        }
+
+        let empty: Chart = parse("[]");
+        assert_eq!(empty, Chart::Daily(vec![]));
+    }
+
+    #[test]
+    fn company() {
+        let company: Company = parse(fixture!("company"));
+        assert_eq!(company.symbol, "AAPL");
+        assert_eq!(company.industry, "Computer Hardware");
+        assert_eq!(company.website, "http://www.apple.com");
+        assert_eq!(company.ceo, "Timothy D. Cook");
+        assert_eq!(company.issue_type, "cs");
+        assert_eq!(company.sector, "Technology");
+    }
+
+    #[test]
+    fn delayed_quote() {
+        let quote: DelayedQuote = parse(fixture!("delayed_quote"));
+        assert_eq!(quote.symbol, "AAPL");
+        assert_eq!(quote.delayed_price, 143.08);
+        assert_eq!(quote.delayed_size, 200.0);
+        assert_eq!(quote.delayed_price_time, 1498762739791.0);
+        assert_eq!(quote.processed_time, 1498763640156.0);
+    }
+
+    #[test]
+    fn dividends() {
+        let dividends: Vec<Dividend> = parse(fixture!("dividends"));
+        assert_eq!(dividends.len(), 1);
+        assert_eq!(dividends[0].ex_date, "2017-08-10");
+        assert_eq!(dividends[0].payment_date, "2017-08-17");
+        assert_eq!(dividends[0].amount, 0.63);
+        assert_eq!(dividends[0].dtype, "Dividend income");
+        assert_eq!(dividends[0].qualified, "Q");
+    }
+
+    #[test]
+    fn earnings() {
+        let earnings: Earnings = parse(fixture!("earnings"));
+        assert_eq!(earnings.symbol, "AAPL");
+        assert_eq!(earnings.earnings.len(), 1);
+        let earning = &earnings.earnings[0];
+        assert_eq!(earning.actual_eps, 2.1);
+        assert_eq!(earning.consensus_eps, 2.02);
+        assert_eq!(earning.estimated_eps, 2.02);
+        assert_eq!(earning.announce_time, "AMC");
+        assert_eq!(earning.eps_surprise_dollar, 0.08);
+        assert_eq!(earning.eps_report_date, "2017-05-02");
+        assert_eq!(earning.fiscal_period, "Q2 2017");
+    }
+
+    #[test]
+    fn effective_spread() {
+        let spreads: Vec<EffectiveSpread> = parse(fixture!("effective_spread"));
+        assert_eq!(spreads.len(), 1);
+        assert_eq!(spreads[0].volume, 4899.0);
+        assert_eq!(spreads[0].venue, "XCHI");
+        assert_eq!(spreads[0].venue_name, "CHX");
+        assert_eq!(spreads[0].effective_spread, 0.02253725);
+        assert_eq!(spreads[0].effective_quoted, 0.9539362);
+    }
+
+    #[test]
+    fn financials() {
+        let financials: Financials = parse(fixture!("financials"));
+        assert_eq!(financials.symbol, "AAPL");
+        assert_eq!(financials.financials.len(), 1);
+        let financial = &financials.financials[0];
+        assert_eq!(financial.report_date, "2017-03-31");
+        assert_eq!(financial.gross_profit, 20591000000.0);
+        assert_eq!(financial.research_and_development, 2776000000.0);
+        assert_eq!(financial.shareholder_equity, 134082000000.0);
+        assert_eq!(financial.cash_change, -1214000000.0);
+    }
+
+    #[test]
+    fn threshold_securities() {
+        let securities: Vec<IEXRegulationSHOThresholdSecurity> =
+            parse(fixture!("threshold_securities"));
+        assert_eq!(securities.len(), 1);
+        assert_eq!(securities[0].trade_date, "20171013");
+        assert_eq!(securities[0].symbolin_inet_symbology, "ZEXIT");
+        assert_eq!(securities[0].symbolin_cqs_symbology, "ZEXIT");
+        assert_eq!(securities[0].symbolin_cms_symbology, "ZEXIT");
+        assert_eq!(securities[0].security_name, "ZEXIT Test Company Class A");
+    }
+
+    #[test]
+    fn short_interest() {
+        let interest: Vec<IEXShortInterest> = parse(fixture!("short_interest"));
+        assert_eq!(interest.len(), 1);
+        assert_eq!(interest[0].settlement_date, "20171013");
+        assert_eq!(interest[0].symbolin_inet_symbology, "ZEXIT");
+        assert_eq!(interest[0].company_name, "ZEXIT Test Company");
+        assert_eq!(interest[0].current_short_interest, "4350");
+        assert_eq!(interest[0].previous_short_interest, "4150");
+        assert_eq!(interest[0].daysto_cover, "0.44");
+        assert_eq!(interest[0].revision_flag, "N");
+    }
+
+    #[test]
+    fn stats() {
+        let stats: Stats = parse(fixture!("stats"));
+        assert_eq!(stats.symbol, "AAPL");
+        assert_eq!(stats.company_name, "Apple Inc.");
+        assert_eq!(stats.marketcap, 751627174400.0);
+        assert_eq!(stats.week52high, 159.65);
+        assert_eq!(stats.latest_eps, 8.29);
+        assert_eq!(stats.latest_eps_date, "2016-09-30");
+        assert_eq!(stats.consensus_eps, 1.56);
+        assert_eq!(stats.eps_surprise_percent, 6.4103);
+        assert_eq!(stats.eps_surprise_dollar, Some(0.1));
+        assert_eq!(stats.ebitda, 69742000000.0);
+        assert_eq!(stats.ttm_eps, 8.81);
+        assert_eq!(stats.return_on_capital, None);
+        assert_eq!(stats.day200_moving_avg, 144.82);
+        assert_eq!(stats.day30_change_percent, -0.0075);
+    }
+
+    #[test]
+    fn logo() {
+        let logo: Logo = parse(fixture!("logo"));
+        assert_eq!(
+            logo.url,
+            "https://storage.googleapis.com/iex/api/logos/AAPL.png"
+        );
+    }
+
+    #[test]
+    fn news() {
+        let news: Vec<News> = parse(fixture!("news"));
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].datetime.to_rfc3339(), "2017-06-29T13:14:22-04:00");
+        assert_eq!(news[0].source, "Benzinga");
+        assert_eq!(
+            news[0].related,
+            vec!["AAPL", "AMZN", "GOOG", "GOOGL", "MSFT"]
+        );
+    }
+
+    #[test]
+    fn news_without_related() {
+        let news: Vec<News> = parse(fixture!("news_market"));
+        assert_eq!(news[1].related, Vec::<String>::new());
+        assert_eq!(
+            parse::<Vec<News>>(&serde_json::to_string(&news).unwrap()),
+            news
+        );
+    }
+
+    #[test]
+    fn ohlc() {
+        let ohlc: OHLC = parse(fixture!("ohlc"));
+        assert_eq!(ohlc.open.price, 154.0);
+        assert_eq!(ohlc.open.time, 1506605400394.0);
+        assert_eq!(ohlc.close.price, 153.28);
+        assert_eq!(ohlc.high, Some(154.8));
+        assert_eq!(ohlc.low, Some(153.25));
+    }
+
+    #[test]
+    fn previous() {
+        let previous: Previous = parse(fixture!("previous"));
+        assert_eq!(previous.symbol, "AAPL");
+        assert_eq!(previous.date, "2017-09-19");
+        assert_eq!(previous.close, 158.73);
+        assert_eq!(previous.volume, 20810632.0);
+        assert_eq!(previous.change_percent, 0.038);
+        assert_eq!(previous.vwap, 158.9624);
+    }
+
+    #[test]
+    fn relevant() {
+        let relevant: Relevant = parse(fixture!("relevant"));
+        assert!(relevant.peers);
+        assert_eq!(relevant.symbols.len(), 7);
+        assert_eq!(relevant.symbols[0], "MSFT");
+    }
+
+    #[test]
+    fn splits() {
+        let splits: Vec<Split> = parse(fixture!("splits"));
+        assert_eq!(splits.len(), 1);
+        assert_eq!(splits[0].ex_date, "2014-06-09");
+        assert_eq!(splits[0].ratio, 0.142857);
+        assert_eq!(splits[0].to_factor, 7.0);
+        assert_eq!(splits[0].for_factor, 1.0);
+    }
+
+    #[test]
+    fn minute_bars() {
+        let bars: Vec<MinuteBar> = parse(fixture!("chart_date"));
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].date, "20171215");
+        assert_eq!(bars[0].minute, "09:30");
+        assert_eq!(bars[0].label, "09:30 AM");
+        assert_eq!(bars[0].open, Some(143.98));
+        assert_eq!(bars[0].number_of_trades, 20.0);
+        assert_eq!(bars[0].market_notional, 441740.275);
+        assert_eq!(bars[1].high, Some(-1.0));
+        assert_eq!(bars[1].open, None);
+        assert_eq!(bars[1].change_over_time, None);
+        assert_eq!(bars[1].market_volume, 27650.0);
+        assert_eq!(bars[1].market_number_of_trades, 168.0);
+        assert_eq!(bars[1].market_close, Some(143.72));
+    }
+
+    #[test]
+    fn volume_by_venue() {
+        let venues: Vec<VolumeByVenue> = parse(fixture!("volume_by_venue"));
+        assert_eq!(venues.len(), 2);
+        assert_eq!(venues[1].volume, 100.0);
+        assert_eq!(venues[1].venue, "IEXG");
+        assert_eq!(venues[1].venue_name, "IEX");
+        assert_eq!(venues[1].market_percent, 0.01);
+        assert_eq!(venues[1].avg_market_percent, 0.0245);
+    }
+
+    #[test]
+    fn corporate_actions() {
+        let actions: Vec<CorporateAction> = parse(fixture!("corporate_actions"));
+        assert_eq!(actions[0].record_id, "CA20171108153808065");
+        assert_eq!(actions[0].effective_date, "2017-11-10");
+        assert_eq!(actions[0].current_symbolin_cms_symbology, "ZEXIT PR");
+        assert_eq!(actions[0].new_listing_center, "V");
+        assert_eq!(actions[0].current_luld_tier_indicator, "0");
+        assert_eq!(actions[0].ipo_flag, "N");
+        assert_eq!(actions[0].delisting_reason, "");
+    }
+
+    #[test]
+    fn daily_list_dividends() {
+        let dividends: Vec<DailyListDividend> = parse(fixture!("daily_list_dividends"));
+        assert_eq!(dividends[0].event_type, "NEW");
+        assert_eq!(dividends[0].symbolin_inet_symbology, "ZEXIT");
+        assert_eq!(dividends[0].ex_date, "2017-11-09");
+        assert_eq!(dividends[0].dividend_type_id, "XS");
+        assert_eq!(dividends[0].stock_adjustment_factor, "1.1");
+    }
+
+    #[test]
+    fn next_day_ex_date() {
+        let ex_dates: Vec<NextDayExDate> = parse(fixture!("next_day_ex_date"));
+        assert_eq!(ex_dates[0].ex_date, "2017-11-09");
+        assert_eq!(ex_dates[0].qualified_dividend, "Y");
+        assert_eq!(ex_dates[0].election_or_expiration_date, "");
+    }
+
+    #[test]
+    fn symbol_directory() {
+        let directory: Vec<SymbolDirectoryEntry> = parse(fixture!("symbol_directory"));
+        assert_eq!(directory[0].security_name, "ZEXIT Common Stock");
+        assert_eq!(directory[0].test_issue, "Y");
+        assert_eq!(directory[0].sic, "5678");
+        assert_eq!(directory[0].adjusted_previous_closing_price, "24.6");
+    }
+
+    #[test]
+    fn hist() {
+        let hist: HashMap<String, Vec<HistFile>> = parse(fixture!("hist"));
+        let files = &hist["20180127"];
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].feed, "TOPS");
+        assert_eq!(files[0].size, 1_364_238_502);
+        assert_eq!(files[1].file_name(), "20180127_IEXTP1_DEEP1.0.pcap.gz");
+
+        let files: Vec<HistFile> = parse(fixture!("hist_date"));
+        assert_eq!(files[0].date, "20180127");
+        assert_eq!(files[1].size, 2_437_419_371);
+    }
+
+    #[test]
+    fn tops() {
+        let tops: Vec<Tops> = parse(fixture!("tops"));
+        assert_eq!(tops.len(), 2);
+        assert_eq!(tops[0].symbol, "SNAP");
+        assert_eq!(tops[0].bid_price, 10.1);
+        assert_eq!(tops[0].ask_size, 100.0);
+        assert_eq!(tops[0].last_sale_time, 1480446905681.0);
+        assert_eq!(tops[1].sector, "softwareservices");
+        assert_eq!(tops[1].security_type, "commonstock");
+    }
+
+    #[test]
+    fn last() {
+        let last: Vec<Last> = parse(fixture!("last"));
+        assert_eq!(last[0].symbol, "SNAP");
+        assert_eq!(last[0].price, 111.76);
+        assert_eq!(last[0].size, 5.0);
+        assert_eq!(last[0].time, 1480446905681.0);
+    }
+
+    #[test]
+    fn deep() {
+        let deep: Deep = parse(fixture!("deep"));
+        assert_eq!(deep.symbol, "SNAP");
+        assert_eq!(deep.bids.len(), 2);
+        assert_eq!(deep.asks[0].price, 22.98);
+        assert_eq!(deep.system_event.system_event, "R");
+        assert_eq!(deep.trading_status.status, "T");
+        assert!(!deep.op_halt_status.is_halted);
+        assert!(!deep.ssr_status.is_ssr);
+        assert_eq!(deep.security_event.security_event, "MarketOpen");
+        assert_eq!(deep.trades[0].trade_id, 517341294.0);
+        assert_eq!(deep.trade_breaks[0].price, 22.98);
+    }
+
+    #[test]
+    fn deep_statuses() {
+        let trading: HashMap<String, TradingStatus> = parse(fixture!("deep_trading_status"));
+        assert_eq!(trading["SNAP"].reason, "NA");
+        let halt: HashMap<String, OpHaltStatus> = parse(fixture!("deep_op_halt_status"));
+        assert!(halt["SNAP"].is_halted);
+        let ssr: HashMap<String, SSRStatus> = parse(fixture!("deep_ssr_status"));
+        assert!(ssr["SNAP"].is_ssr);
+        assert_eq!(ssr["SNAP"].detail, "N");
+    }
+
+    #[test]
+    fn auction() {
+        let auctions: HashMap<String, Auction> = parse(fixture!("deep_auction"));
+        let auction = &auctions["ZIEXT"];
+        assert_eq!(auction.auction_type, "Open");
+        assert_eq!(auction.paired_shares, 3600.0);
+        assert_eq!(auction.upper_collar_price, 1.75);
+        assert_eq!(auction.start_time, "09:30:00");
+    }
+
+    #[test]
+    fn official_price() {
+        let prices: HashMap<String, OfficialPrice> = parse(fixture!("deep_official_price"));
+        assert_eq!(prices["ZIEXT"].price_type, "Open");
+        assert_eq!(prices["ZIEXT"].price, 1.05);
+    }
+
+    #[test]
+    fn deep_stream() {
+        let messages: Vec<DeepMessage> = parse(fixture!("deep_stream"));
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].symbol, "SNAP");
+        match messages[0].update {
+            DeepUpdate::Book(ref book) => {
+                assert_eq!(book.bids[0].price, 111.76);
+                assert!(book.asks.is_empty());
+            }
+            ref update => panic!("unexpected update: {:?}", update),
+        }
+        match messages[1].update {
+            DeepUpdate::Trades(ref trade) => assert_eq!(trade.trade_id, 2279736.0),
+            ref update => panic!("unexpected update: {:?}", update),
+        }
+        match messages[2].update {
+            DeepUpdate::SsrStatus(ref status) => assert!(!status.is_ssr),
+            ref update => panic!("unexpected update: {:?}", update),
+        }
+    }
+
+    #[test]
+    fn intraday_stats() {
+        let stats: IntradayStats = parse(fixture!("stats_intraday"));
+        assert_eq!(stats.volume.value, 26908038.0);
+        assert_eq!(stats.symbols_traded.value, 4089.0);
+        assert_eq!(stats.market_share.last_updated, 1480433817323.0);
+    }
+
+    #[test]
+    fn daily_stats() {
+        let recent: Vec<DailyStats> = parse(fixture!("stats_recent"));
+        assert_eq!(recent[0].date, "2017-01-11");
+        assert_eq!(recent[0].market_share, 0.01769);
+        assert!(!recent[0].is_halfday);
+
+        let daily: Vec<DailyStats> = parse(fixture!("stats_historical_daily"));
+        assert!(!daily[0].is_halfday);
+        assert!(daily[1].is_halfday);
+    }
+
+    #[test]
+    fn record_stats() {
+        let records: RecordStats = parse(fixture!("stats_records"));
+        assert_eq!(records.volume.record_value, 233000477.0);
+        assert_eq!(records.volume.record_date, "2016-01-20");
+        assert_eq!(records.notional.avg30_value, 6193041131.7);
+    }
+
+    #[test]
+    fn historical_stats() {
+        let stats: Vec<HistoricalStats> = parse(fixture!("stats_historical"));
+        assert_eq!(stats[0].date, "2016-05");
+        assert_eq!(stats[0].average_daily_volume, 112247378.5);
+        assert_eq!(stats[0].bin10000_trades, 4666.0);
+        assert_eq!(stats[0].small_cap_percent, 0.18609);
+    }
+
+    #[test]
+    fn market() {
+        let market: Vec<MarketVolume> = parse(fixture!("market"));
+        assert_eq!(market.len(), 2);
+        assert_eq!(market[0].mic, "TRF");
+        assert_eq!(market[0].tape_a, 305187928.0);
+        assert_eq!(market[1].venue_name, "NYSE Arca");
+        assert_eq!(market[1].last_updated, 1480433817317.0);
+    }
+
+    #[test]
+    fn sector_performance() {
+        let sectors: Vec<SectorPerformance> = parse(fixture!("sector_performance"));
+        assert_eq!(sectors[0].performance_type, "sector");
+        assert_eq!(sectors[0].name, "Industrials");
+        assert_eq!(sectors[1].performance, -0.00124);
+        assert_eq!(sectors[1].last_updated, 1533672000437.0);
+    }
+
+    #[test]
+    fn batch() {
+        let batch: HashMap<String, BatchResult> = parse(fixture!("batch"));
+        assert_eq!(batch.len(), 2);
+
+        let aapl = &batch["AAPL"];
+        assert_eq!(aapl.quote.as_ref().unwrap().symbol, "AAPL");
+        assert_eq!(aapl.news.as_ref().unwrap()[0].source, "Benzinga");
+        assert_eq!(aapl.chart.as_ref().unwrap().daily().unwrap().len(), 2);
+        assert_eq!(aapl.price, None);
+        assert_eq!(aapl.delayed_quote, None);
+
+        let fb = &batch["FB"];
+        assert_eq!(fb.quote.as_ref().unwrap().company_name, "Facebook Inc.");
+        assert_eq!(fb.news, Some(vec![]));
+        assert_eq!(fb.chart.as_ref().unwrap().daily().unwrap()[0].close, 171.7);
+    }
+
+    #[test]
+    fn symbols() {
+        let symbols: Vec<Symbol> = parse(fixture!("symbols"));
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].symbol, "A");
+        assert_eq!(
+            symbols[0].name,
+            Some(String::from("Agilent Technologies Inc."))
+        );
+        assert!(symbols[0].is_enabled);
+        assert_eq!(symbols[0].symbol_type, Some(SymbolType::CommonStock));
+        assert_eq!(symbols[1].symbol_type, Some(SymbolType::ETF));
+        assert_eq!(symbols[1].iex_id, Some(String::from("7726")));
+    }
+
+    #[test]
+    fn iex_symbols() {
+        let symbols: Vec<Symbol> = parse(fixture!("iex_symbols"));
+        assert_eq!(symbols[0].symbol, "A");
+        assert_eq!(symbols[0].name, None);
+        assert_eq!(symbols[0].symbol_type, None);
+    }
+
+    #[test]
+    fn international_symbols() {
+        let symbols: Vec<Symbol> = parse(fixture!("international_symbols"));
+        assert_eq!(symbols[0].exchange, Some(String::from("TSX")));
+        assert_eq!(symbols[0].region, Some(String::from("CA")));
+        assert_eq!(symbols[0].currency, Some(String::from("CAD")));
+        assert_eq!(symbols[1].symbol_type, Some(SymbolType::ClosedEndFund));
+
+        // Type codes are written back exactly as the API sent them.
+        let json = serde_json::to_string(&symbols).unwrap();
+        let original: serde_json::Value = parse(fixture!("international_symbols"));
+        let round_trip: serde_json::Value = parse(&json);
+        assert_eq!(round_trip[1]["type"], original[1]["type"]);
+        assert_eq!(round_trip[0]["type"], original[0]["type"]);
+    }
+
+    #[test]
+    fn symbol_type_codes() {
+        let types: Vec<SymbolType> = parse(r#"["ad", "re", "cef", "wt", "N/A", "zz"]"#);
+        assert_eq!(
+            types,
+            vec![
+                SymbolType::ADR,
+                SymbolType::REIT,
+                SymbolType::ClosedEndFund,
+                SymbolType::Warrant,
+                SymbolType::NotApplicable,
+                SymbolType::Other(String::from("zz")),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&types).unwrap(),
+            r#"["ad","re","cef","wt","N/A","zz"]"#
+        );
+    }
 }
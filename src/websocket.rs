@@ -0,0 +1,624 @@
+//! A minimal blocking WebSocket client, enough to carry the socket.io connection IEX streams over.
+//!
+//! Only text messages are handed back. Pings are answered as they're read, and binary messages
+//! are dropped, since socket.io only uses them for attachments IEX never sends.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use base64;
+use byteorder::{BigEndian, ByteOrder};
+use native_tls::{TlsConnector, TlsStream};
+use rand;
+use reqwest::Url;
+
+use {IexError, Result};
+
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_HEAD_LEN: usize = 16 * 1024;
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+pub(crate) const OP_CONTINUATION: u8 = 0x0;
+pub(crate) const OP_TEXT: u8 = 0x1;
+pub(crate) const OP_BINARY: u8 = 0x2;
+pub(crate) const OP_CLOSE: u8 = 0x8;
+pub(crate) const OP_PING: u8 = 0x9;
+pub(crate) const OP_PONG: u8 = 0xa;
+
+const CLOSE_NORMAL: u16 = 1000;
+
+enum Connection {
+    Plain(TcpStream),
+    Tls(TlsStream<TcpStream>),
+}
+
+impl Connection {
+    fn tcp(&self) -> &TcpStream {
+        match self {
+            Connection::Plain(stream) => stream,
+            Connection::Tls(stream) => stream.get_ref(),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A single WebSocket frame.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Frame {
+    pub fin: bool,
+    pub opcode: u8,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Decodes the frame at the start of `buf`, returning it and the number of bytes it took up,
+    /// or `None` if `buf` doesn't hold a whole frame yet.
+    pub fn decode(buf: &[u8]) -> Result<Option<(Frame, usize)>> {
+        if buf.len() < 2 {
+            return Ok(None);
+        }
+        let masked = buf[1] & 0x80 != 0;
+        let (len, mut offset) = match buf[1] & 0x7f {
+            126 if buf.len() >= 4 => (u64::from(BigEndian::read_u16(&buf[2..])), 4),
+            127 if buf.len() >= 10 => (BigEndian::read_u64(&buf[2..]), 10),
+            126 | 127 => return Ok(None),
+            len => (u64::from(len), 2),
+        };
+        if len > MAX_MESSAGE_LEN as u64 {
+            return Err(IexError::Stream(format!("{} byte frame is too long", len)));
+        }
+        let len = len as usize;
+
+        let mask = if masked {
+            if buf.len() < offset + 4 {
+                return Ok(None);
+            }
+            offset += 4;
+            Some(&buf[offset - 4..offset])
+        } else {
+            None
+        };
+        if buf.len() < offset + len {
+            return Ok(None);
+        }
+        let mut payload = buf[offset..offset + len].to_vec();
+        if let Some(mask) = mask {
+            apply_mask(&mut payload, mask);
+        }
+        let frame = Frame {
+            fin: buf[0] & 0x80 != 0,
+            opcode: buf[0] & 0x0f,
+            payload: payload,
+        };
+        Ok(Some((frame, offset + len)))
+    }
+
+    /// Encodes the frame, masking the payload with `mask` if given. Clients have to mask every
+    /// frame they send, and servers mustn't mask any.
+    pub fn encode(&self, mask: Option<[u8; 4]>) -> Vec<u8> {
+        let mut out = vec![if self.fin { 0x80 } else { 0 } | self.opcode];
+        let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+        let len = self.payload.len();
+        if len < 126 {
+            out.push(mask_bit | len as u8);
+        } else if len <= usize::from(u16::max_value()) {
+            out.push(mask_bit | 126);
+            let mut bytes = [0; 2];
+            BigEndian::write_u16(&mut bytes, len as u16);
+            out.extend_from_slice(&bytes);
+        } else {
+            out.push(mask_bit | 127);
+            let mut bytes = [0; 8];
+            BigEndian::write_u64(&mut bytes, len as u64);
+            out.extend_from_slice(&bytes);
+        }
+
+        let mut payload = self.payload.clone();
+        if let Some(mask) = mask {
+            out.extend_from_slice(&mask);
+            apply_mask(&mut payload, &mask);
+        }
+        out.extend(payload);
+        out
+    }
+}
+
+fn apply_mask(payload: &mut [u8], mask: &[u8]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+}
+
+/// A client connection to a WebSocket server.
+pub(crate) struct WebSocket {
+    conn: Connection,
+    buf: Vec<u8>,
+    // The opcode and payload so far of a message that's been split across frames.
+    partial: Option<(u8, Vec<u8>)>,
+}
+
+impl WebSocket {
+    /// Connects to a `ws://` or `wss://` URL and performs the opening handshake. `timeout` applies
+    /// to connecting and to each read and write, and can be changed with `set_read_timeout` once
+    /// connected.
+    pub fn connect(url: &Url, timeout: Duration) -> Result<WebSocket> {
+        let secure = match url.scheme() {
+            "ws" | "http" => false,
+            "wss" | "https" => true,
+            scheme => {
+                return Err(IexError::Stream(format!(
+                    "can't open a WebSocket to a {} URL",
+                    scheme
+                )))
+            }
+        };
+        let host = url
+            .host_str()
+            .ok_or_else(|| IexError::Stream(format!("{} has no host", url)))?;
+        let port = url
+            .port_or_known_default()
+            .unwrap_or(if secure { 443 } else { 80 });
+
+        let tcp = TcpStream::connect((host, port))?;
+        tcp.set_read_timeout(Some(timeout))?;
+        tcp.set_write_timeout(Some(timeout))?;
+        let conn = if secure {
+            let connector = TlsConnector::builder()
+                .and_then(|builder| builder.build())
+                .map_err(|err| IexError::Transport(err.into()))?;
+            let tls = connector.connect(host, tcp).map_err(|err| {
+                IexError::Transport(format_err!("TLS handshake with {} failed: {}", host, err))
+            })?;
+            Connection::Tls(tls)
+        } else {
+            Connection::Plain(tcp)
+        };
+
+        let mut socket = WebSocket {
+            conn: conn,
+            buf: Vec::new(),
+            partial: None,
+        };
+        socket.handshake(url, host)?;
+        Ok(socket)
+    }
+
+    fn handshake(&mut self, url: &Url, host: &str) -> Result<()> {
+        let key = base64::encode(&rand::random::<[u8; 16]>());
+        let mut target = String::from(url.path());
+        if let Some(query) = url.query() {
+            target.push('?');
+            target.push_str(query);
+        }
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => String::from(host),
+        };
+        write!(
+            self.conn,
+            "GET {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Upgrade: websocket\r\n\
+             Connection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\n\
+             Sec-WebSocket-Version: 13\r\n\r\n",
+            target, host, key
+        )?;
+        self.conn.flush()?;
+
+        let head = self.read_head()?;
+        let mut lines = head.split("\r\n");
+        let status = lines.next().unwrap_or("");
+        if status.split(' ').nth(1) != Some("101") {
+            return Err(IexError::Stream(format!(
+                "server refused the WebSocket upgrade: {}",
+                status
+            )));
+        }
+        let accept = lines
+            .filter_map(|line| {
+                let mut parts = line.splitn(2, ':');
+                match (parts.next(), parts.next()) {
+                    (Some(name), Some(value))
+                        if name.trim().eq_ignore_ascii_case("sec-websocket-accept") =>
+                    {
+                        Some(value.trim())
+                    }
+                    _ => None,
+                }
+            })
+            .next();
+        if accept != Some(accept_key(&key).as_str()) {
+            return Err(IexError::Stream(String::from(
+                "server answered the WebSocket upgrade with the wrong Sec-WebSocket-Accept",
+            )));
+        }
+        Ok(())
+    }
+
+    // Reads up to the blank line after the handshake response's headers. Anything after it is
+    // the start of the first frame, so it stays in the buffer.
+    fn read_head(&mut self) -> Result<String> {
+        loop {
+            if let Some(end) = self.buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                let head: Vec<u8> = self.buf.drain(..end + 4).collect();
+                return Ok(String::from_utf8_lossy(&head).into_owned());
+            }
+            if self.buf.len() > MAX_HEAD_LEN {
+                return Err(IexError::Stream(String::from(
+                    "WebSocket handshake response is too long",
+                )));
+            }
+            if self.fill()? == 0 {
+                return Err(IexError::Stream(String::from(
+                    "connection closed during the WebSocket handshake",
+                )));
+            }
+        }
+    }
+
+    pub fn set_read_timeout(&self, timeout: Duration) -> Result<()> {
+        Ok(self.conn.tcp().set_read_timeout(Some(timeout))?)
+    }
+
+    /// The next text message, or `None` if nothing arrived within the read timeout. A message
+    /// that's only partly arrived is kept for the next call.
+    pub fn read_text(&mut self) -> Result<Option<String>> {
+        loop {
+            while let Some((frame, len)) = Frame::decode(&self.buf)? {
+                self.buf.drain(..len);
+                if let Some((opcode, payload)) = self.message(frame)? {
+                    if opcode == OP_TEXT {
+                        return String::from_utf8(payload).map(Some).map_err(|_| {
+                            IexError::Stream(String::from("text message isn't valid UTF-8"))
+                        });
+                    }
+                }
+            }
+            match self.fill() {
+                Ok(0) => {
+                    return Err(IexError::Stream(String::from(
+                        "server closed the connection",
+                    )))
+                }
+                Ok(_) => {}
+                Err(IexError::Io(ref err))
+                    if err.kind() == io::ErrorKind::WouldBlock
+                        || err.kind() == io::ErrorKind::TimedOut =>
+                {
+                    return Ok(None)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    // Handles a control frame, or adds a data frame to the message being built. Returns the
+    // message once its last frame has arrived.
+    fn message(&mut self, frame: Frame) -> Result<Option<(u8, Vec<u8>)>> {
+        match frame.opcode {
+            OP_PING => {
+                self.send(OP_PONG, frame.payload)?;
+                Ok(None)
+            }
+            OP_PONG => Ok(None),
+            OP_CLOSE => {
+                let _ = self.send(OP_CLOSE, frame.payload.iter().take(2).cloned().collect());
+                let reason = String::from_utf8_lossy(frame.payload.get(2..).unwrap_or(&[]));
+                Err(IexError::Stream(format!(
+                    "server closed the WebSocket: {}",
+                    reason
+                )))
+            }
+            OP_TEXT | OP_BINARY if self.partial.is_none() => {
+                if frame.fin {
+                    Ok(Some((frame.opcode, frame.payload)))
+                } else {
+                    self.partial = Some((frame.opcode, frame.payload));
+                    Ok(None)
+                }
+            }
+            OP_CONTINUATION if self.partial.is_some() => {
+                let done = {
+                    let partial = self.partial.as_mut().unwrap();
+                    partial.1.extend(frame.payload);
+                    if partial.1.len() > MAX_MESSAGE_LEN {
+                        return Err(IexError::Stream(String::from("message is too long")));
+                    }
+                    frame.fin
+                };
+                Ok(if done { self.partial.take() } else { None })
+            }
+            opcode => Err(IexError::Stream(format!(
+                "unexpected WebSocket frame with opcode {:#x}",
+                opcode
+            ))),
+        }
+    }
+
+    pub fn send_text(&mut self, text: &str) -> Result<()> {
+        self.send(OP_TEXT, text.as_bytes().to_vec())
+    }
+
+    fn send(&mut self, opcode: u8, payload: Vec<u8>) -> Result<()> {
+        let frame = Frame {
+            fin: true,
+            opcode: opcode,
+            payload: payload,
+        };
+        self.conn.write_all(&frame.encode(Some(rand::random())))?;
+        Ok(self.conn.flush()?)
+    }
+
+    /// Starts the closing handshake. The server's reply isn't waited for.
+    pub fn close(&mut self) {
+        let mut code = vec![0; 2];
+        BigEndian::write_u16(&mut code, CLOSE_NORMAL);
+        let _ = self.send(OP_CLOSE, code);
+    }
+
+    fn fill(&mut self) -> Result<usize> {
+        let mut chunk = [0; READ_CHUNK_LEN];
+        let len = self.conn.read(&mut chunk)?;
+        self.buf.extend_from_slice(&chunk[..len]);
+        Ok(len)
+    }
+}
+
+/// The `Sec-WebSocket-Accept` a server should answer `key` with.
+pub(crate) fn accept_key(key: &str) -> String {
+    base64::encode(&sha1(format!("{}{}", key, ACCEPT_GUID).as_bytes()))
+}
+
+// The handshake is the only thing that needs SHA-1, so it's not worth a dependency.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    let mut bit_len = [0; 8];
+    BigEndian::write_u64(&mut bit_len, data.len() as u64 * 8);
+    message.extend_from_slice(&bit_len);
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+            *word = BigEndian::read_u32(bytes);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let (mut a, mut b, mut c, mut d, mut e) =
+            (state[0], state[1], state[2], state[3], state[4]);
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (s, v) in state.iter_mut().zip(&[a, b, c, d, e]) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(&state) {
+        BigEndian::write_u32(bytes, *word);
+    }
+    digest
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// The server end of a WebSocket connection, for standing in for IEX in tests.
+    pub(crate) struct TestServer {
+        stream: TcpStream,
+        buf: Vec<u8>,
+    }
+
+    impl TestServer {
+        /// Accepts the next connection on `listener` and completes the opening handshake,
+        /// returning the server end and the request target the client asked for.
+        pub fn accept(listener: &TcpListener) -> (TestServer, String) {
+            let (stream, _) = listener.accept().unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut server = TestServer {
+                stream: stream,
+                buf: Vec::new(),
+            };
+            while !server.buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                server.fill();
+            }
+            let end = server
+                .buf
+                .windows(4)
+                .position(|w| w == b"\r\n\r\n")
+                .unwrap();
+            let head: Vec<u8> = server.buf.drain(..end + 4).collect();
+            let head = String::from_utf8(head).unwrap();
+            let target = String::from(head.split(' ').nth(1).unwrap());
+            let key = head
+                .lines()
+                .find(|line| line.to_lowercase().starts_with("sec-websocket-key:"))
+                .map(|line| line[18..].trim())
+                .unwrap();
+            write!(
+                server.stream,
+                "HTTP/1.1 101 Switching Protocols\r\n\
+                 Upgrade: websocket\r\n\
+                 Connection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(key)
+            )
+            .unwrap();
+            (server, target)
+        }
+
+        pub fn send_text(&mut self, text: &str) {
+            let frame = Frame {
+                fin: true,
+                opcode: OP_TEXT,
+                payload: text.as_bytes().to_vec(),
+            };
+            self.stream.write_all(&frame.encode(None)).unwrap();
+        }
+
+        /// The next frame from the client, which has to be masked.
+        pub fn read_frame(&mut self) -> Frame {
+            loop {
+                if self.buf.len() >= 2 {
+                    assert!(self.buf[1] & 0x80 != 0, "client frames must be masked");
+                }
+                if let Some((frame, len)) = Frame::decode(&self.buf).unwrap() {
+                    self.buf.drain(..len);
+                    return frame;
+                }
+                self.fill();
+            }
+        }
+
+        pub fn read_text(&mut self) -> String {
+            let frame = self.read_frame();
+            assert_eq!(frame.opcode, OP_TEXT);
+            String::from_utf8(frame.payload).unwrap()
+        }
+
+        fn fill(&mut self) {
+            let mut chunk = [0; 1024];
+            let len = self.stream.read(&mut chunk).unwrap();
+            assert!(len > 0, "client closed the connection");
+            self.buf.extend_from_slice(&chunk[..len]);
+        }
+    }
+
+    #[test]
+    fn sha1_digest() {
+        let hex =
+            |digest: [u8; 20]| -> String { digest.iter().map(|b| format!("{:02x}", b)).collect() };
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(sha1(b"The quick brown fox jumps over the lazy dog")),
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+        );
+    }
+
+    #[test]
+    fn accept_key_from_rfc() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        for len in &[0, 125, 126, 65_535, 65_536] {
+            let frame = Frame {
+                fin: true,
+                opcode: OP_BINARY,
+                payload: vec![7; *len],
+            };
+            for mask in &[None, Some([1, 2, 3, 4])] {
+                let bytes = frame.encode(*mask);
+                assert_eq!(
+                    Frame::decode(&bytes).unwrap(),
+                    Some((frame.clone(), bytes.len()))
+                );
+                assert_eq!(Frame::decode(&bytes[..bytes.len() - 1]).unwrap(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = ::std::thread::spawn(move || {
+            let (mut server, target) = TestServer::accept(&listener);
+            assert_eq!(target, "/socket");
+            server.send_text("hello");
+
+            // A ping between the pieces of a fragmented message.
+            let pieces = [
+                (false, OP_TEXT, "wor"),
+                (true, OP_PING, "?"),
+                (true, OP_CONTINUATION, "ld"),
+            ];
+            for &(fin, opcode, payload) in &pieces {
+                let frame = Frame {
+                    fin: fin,
+                    opcode: opcode,
+                    payload: payload.as_bytes().to_vec(),
+                };
+                server.stream.write_all(&frame.encode(None)).unwrap();
+            }
+            assert_eq!(server.read_frame().opcode, OP_PONG);
+            assert_eq!(server.read_text(), "from the client");
+            assert_eq!(server.read_frame().opcode, OP_CLOSE);
+        });
+
+        let url = Url::parse(&format!("ws://{}/socket", addr)).unwrap();
+        let mut socket = WebSocket::connect(&url, Duration::from_secs(5)).unwrap();
+        assert_eq!(socket.read_text().unwrap(), Some(String::from("hello")));
+        assert_eq!(socket.read_text().unwrap(), Some(String::from("world")));
+        socket.send_text("from the client").unwrap();
+
+        socket.set_read_timeout(Duration::from_millis(10)).unwrap();
+        assert_eq!(socket.read_text().unwrap(), None);
+        socket.close();
+        server.join().unwrap();
+    }
+}